@@ -0,0 +1,452 @@
+use crate::error::InkError;
+use crate::frontmatter::FrontMatterEditor;
+use crate::http::fetch_page;
+use crate::links::normalize_path;
+use crate::markdown::{find_section, get_markdown_str};
+use crate::resolve::resolve_note;
+use crate::search::reindex_files;
+use chrono::Local;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Node, Selector};
+use std::fs;
+use std::path::Path;
+
+/// The heading the snapshot of a bookmarked page is kept under.
+const SNAPSHOT_HEADING: &str = "Snapshot";
+
+/// Elements that are never part of the readable article.
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "script", "style", "noscript", "template", "nav", "header", "footer", "aside", "form",
+    "button", "iframe", "svg", "canvas", "dialog",
+];
+
+/// Where the article usually is, most specific first.
+const ARTICLE_SELECTORS: &[&str] = &[
+    "article",
+    "main",
+    r#"[role="main"]"#,
+    "#content",
+    ".post-content",
+    ".entry-content",
+];
+
+fn is_skipped(element: &ElementRef) -> bool {
+    let value = element.value();
+    SKIPPED_ELEMENTS.contains(&value.name())
+        || value.attr("hidden").is_some()
+        || value.attr("aria-hidden") == Some("true")
+}
+
+/// Collapse whitespace runs to a single space, as a browser would.
+fn collapse_whitespace(text: &str) -> String {
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_space = false;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !in_space {
+                collapsed.push(' ');
+            }
+            in_space = true;
+        } else {
+            collapsed.push(c);
+            in_space = false;
+        }
+    }
+    collapsed
+}
+
+/// Wrap the trimmed text in `marker`, keeping surrounding spaces outside it.
+fn wrap_inline(text: &str, marker: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let leading = if text.starts_with(' ') { " " } else { "" };
+    let trailing = if text.ends_with(' ') { " " } else { "" };
+    format!("{leading}{marker}{trimmed}{marker}{trailing}")
+}
+
+/// Converts the readable parts of an HTML page to markdown blocks.
+struct Converter<'a> {
+    base_url: &'a Url,
+    blocks: Vec<String>,
+    inline: String,
+}
+
+impl<'a> Converter<'a> {
+    fn new(base_url: &'a Url) -> Self {
+        Converter {
+            base_url,
+            blocks: Vec::new(),
+            inline: String::new(),
+        }
+    }
+
+    fn absolute_url(&self, href: &str) -> Option<String> {
+        let url = self.base_url.join(href).ok()?;
+        matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+    }
+
+    /// End the current paragraph.
+    fn flush(&mut self) {
+        let paragraph = self
+            .inline
+            .split('\n')
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let paragraph = paragraph.trim();
+        if !paragraph.is_empty() {
+            self.blocks.push(paragraph.to_string());
+        }
+        self.inline.clear();
+    }
+
+    fn push_block(&mut self, block: String) {
+        self.flush();
+        if !block.trim().is_empty() {
+            self.blocks.push(block);
+        }
+    }
+
+    /// Inline markdown for the contents of an element.
+    fn inline_markdown(&self, element: ElementRef) -> String {
+        let mut text = String::new();
+        for child in element.children() {
+            match child.value() {
+                Node::Text(node_text) => text.push_str(&collapse_whitespace(node_text)),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        text.push_str(&self.inline_element(child));
+                    }
+                }
+                _ => {}
+            }
+        }
+        text
+    }
+
+    fn inline_element(&self, element: ElementRef) -> String {
+        if is_skipped(&element) {
+            return String::new();
+        }
+        match element.value().name() {
+            "br" => String::from("\n"),
+            "strong" | "b" => wrap_inline(&self.inline_markdown(element), "**"),
+            "em" | "i" => wrap_inline(&self.inline_markdown(element), "*"),
+            "code" | "kbd" | "samp" => {
+                let code = collapse_whitespace(&element.text().collect::<String>());
+                wrap_inline(&code, "`")
+            }
+            "a" => {
+                let text = self.inline_markdown(element);
+                let href = element.value().attr("href").and_then(|href| {
+                    if href.starts_with('#') {
+                        None
+                    } else {
+                        self.absolute_url(href)
+                    }
+                });
+                match href {
+                    Some(href) if !text.trim().is_empty() => {
+                        format!("[{}]({href})", text.trim())
+                    }
+                    _ => text,
+                }
+            }
+            "img" => {
+                let alt = element.value().attr("alt").unwrap_or_default().trim();
+                element
+                    .value()
+                    .attr("src")
+                    .and_then(|src| self.absolute_url(src))
+                    .map(|src| format!("![{alt}]({src})"))
+                    .unwrap_or_default()
+            }
+            _ => self.inline_markdown(element),
+        }
+    }
+
+    fn list(&mut self, element: ElementRef, ordered: bool) {
+        let items: Vec<String> = element
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|child| child.value().name() == "li")
+            .enumerate()
+            .map(|(i, item)| {
+                let marker = if ordered {
+                    format!("{}.", i + 1)
+                } else {
+                    String::from("-")
+                };
+                let text = self.inline_markdown(item).replace('\n', " ");
+                format!("{marker} {}", collapse_whitespace(text.trim()))
+            })
+            .collect();
+        self.push_block(items.join("\n"));
+    }
+
+    fn table(&mut self, element: ElementRef) {
+        let row_selector = Selector::parse("tr").unwrap();
+        let rows: Vec<String> = element
+            .select(&row_selector)
+            .map(|row| {
+                row.children()
+                    .filter_map(ElementRef::wrap)
+                    .map(|cell| collapse_whitespace(self.inline_markdown(cell).trim()))
+                    .collect::<Vec<_>>()
+                    .join(" | ")
+            })
+            .filter(|row| !row.is_empty())
+            .collect();
+        self.push_block(rows.join("\n"));
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        if is_skipped(&element) {
+            return;
+        }
+        let name = element.value().name();
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                // Headings start below the snapshot heading so they stay inside its section
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let text = collapse_whitespace(self.inline_markdown(element).trim());
+                if !text.is_empty() {
+                    self.push_block(format!("{} {text}", "#".repeat((level + 2).min(6))));
+                }
+            }
+            "ul" | "ol" => self.list(element, name == "ol"),
+            "pre" => {
+                let code: String = element.text().collect();
+                self.push_block(format!("```\n{}\n```", code.trim_end()));
+            }
+            "blockquote" => {
+                let mut quote = Converter::new(self.base_url);
+                quote.children(element);
+                quote.flush();
+                let quoted = quote
+                    .blocks
+                    .join("\n\n")
+                    .lines()
+                    .map(|line| format!("> {line}").trim_end().to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.push_block(quoted);
+            }
+            "table" => self.table(element),
+            "hr" => self.push_block(String::from("---")),
+            "p" | "div" | "section" | "article" | "main" | "body" | "figure" | "figcaption"
+            | "dl" | "dt" | "dd" | "details" | "summary" | "address" => {
+                self.flush();
+                self.children(element);
+                self.flush();
+            }
+            _ => {
+                let text = self.inline_element(element);
+                self.inline.push_str(&text);
+            }
+        }
+    }
+
+    fn children(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.inline.push_str(&collapse_whitespace(text)),
+                Node::Element(_) => {
+                    if let Some(child) = ElementRef::wrap(child) {
+                        self.element(child);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The element holding the article: a semantic container when there is one,
+/// otherwise the block with the most paragraph text.
+fn article_root(document: &Html) -> Option<ElementRef<'_>> {
+    let semantic = ARTICLE_SELECTORS.iter().find_map(|selector| {
+        let selector = Selector::parse(selector).unwrap();
+        document.select(&selector).next()
+    });
+    if semantic.is_some() {
+        return semantic;
+    }
+    let paragraph_text = |element: &ElementRef| -> usize {
+        element
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|child| child.value().name() == "p")
+            .map(|paragraph| paragraph.text().map(str::len).sum::<usize>())
+            .sum()
+    };
+    let block_selector = Selector::parse("div, section").unwrap();
+    document
+        .select(&block_selector)
+        .max_by_key(paragraph_text)
+        .filter(|element| paragraph_text(element) > 0)
+        .or_else(|| document.select(&Selector::parse("body").unwrap()).next())
+}
+
+/// Extract the readable article text of a page as markdown.
+pub fn html_to_markdown(html: &str, page_url: &Url) -> String {
+    let document = Html::parse_document(html);
+    let Some(root) = article_root(&document) else {
+        return String::new();
+    };
+    let mut converter = Converter::new(page_url);
+    converter.children(root);
+    converter.flush();
+    converter.blocks.join("\n\n")
+}
+
+/// Split a note body into the text written by hand and its page snapshot.
+pub fn split_snapshot(body: &str) -> (String, Option<&str>) {
+    match find_section(body, SNAPSHOT_HEADING) {
+        Some(range) => {
+            let notes = format!("{}{}", &body[..range.start], &body[range.end..]);
+            (notes, Some(body[range].trim()))
+        }
+        None => (body.to_string(), None),
+    }
+}
+
+/// Put `section` in place of the existing snapshot, or at the end of the note.
+fn replace_snapshot(raw_markdown: &str, section: &str) -> String {
+    let body_start = raw_markdown.len() - FrontMatterEditor::new(raw_markdown).body().len();
+    let Some(existing) = find_section(&raw_markdown[body_start..], SNAPSHOT_HEADING) else {
+        let before = raw_markdown.trim_end();
+        let separator = if before.len() > body_start {
+            "\n\n"
+        } else {
+            "\n"
+        };
+        return format!("{before}{separator}{section}");
+    };
+    let range = body_start + existing.start..body_start + existing.end;
+    let separator = if range.end < raw_markdown.len() {
+        "\n"
+    } else {
+        ""
+    };
+    let mut updated = raw_markdown.to_string();
+    updated.replace_range(range, &format!("{section}{separator}"));
+    updated
+}
+
+/// Download the page a bookmark points to and store its text in the note.
+fn archive_file(path: &Path) -> Result<(), InkError> {
+    let raw_markdown = get_markdown_str(path)?;
+    let mut editor = FrontMatterEditor::new(&raw_markdown);
+    let url = editor
+        .get_str("url")
+        .ok_or_else(|| InkError::parse(path, "not a bookmark, it has no url"))?;
+
+    let page = fetch_page(&url)?;
+    let article = html_to_markdown(&page.body, &page.url);
+    if article.is_empty() {
+        return Err(InkError::parse(
+            path,
+            format!("no readable text found on {}", page.url),
+        ));
+    }
+    let now = Local::now();
+    editor.set("archived", now.to_rfc3339());
+    let section = format!(
+        "## {SNAPSHOT_HEADING}\n\n> Archived from <{}> on {}\n\n{article}\n",
+        page.url,
+        now.format("%Y-%m-%d")
+    );
+    fs::write(path, replace_snapshot(editor.as_str(), &section)).map_err(InkError::io(path))?;
+
+    if let Err(e) = reindex_files(&[normalize_path(path)]) {
+        eprintln!("Could not update the index {e}");
+    }
+    Ok(())
+}
+
+/// Snapshot the page of the bookmark at `path`.
+pub fn archive_path(path: &Path) -> Result<(), InkError> {
+    archive_file(path)?;
+    println!("Archived {}", path.display());
+    Ok(())
+}
+
+/// Snapshot the page of an existing bookmark.
+pub fn archive(note: &str) -> Result<(), InkError> {
+    archive_path(&resolve_note(note)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_markdown() {
+        let html = r##"<html><head><title>Post</title><script>var x = 1;</script></head>
+<body>
+<nav><a href="/">Home</a></nav>
+<article>
+  <h1>The   Title</h1>
+  <p>Some <strong>bold</strong> and <em>italic</em> text with
+     <a href="/other">a link</a>, <a href="#note">an anchor</a> and <code>code()</code>.</p>
+  <ul><li>One</li><li>Two <b>items</b></li></ul>
+  <ol><li>First</li></ol>
+  <pre><code>fn main() {
+    println!("hi");
+}</code></pre>
+  <blockquote><p>Quoted</p><p>Twice</p></blockquote>
+  <img src="pic.png" alt="A picture">
+  <p>Line<br>break</p>
+  <aside>Subscribe!</aside>
+</article>
+<footer>Copyright</footer>
+</body></html>"##;
+        let url = Url::parse("https://example.com/posts/1").unwrap();
+        assert_eq!(
+            html_to_markdown(html, &url),
+            concat!(
+                "### The Title\n\n",
+                "Some **bold** and *italic* text with [a link](https://example.com/other), an anchor and `code()`.\n\n",
+                "- One\n- Two **items**\n\n",
+                "1. First\n\n",
+                "```\nfn main() {\n    println!(\"hi\");\n}\n```\n\n",
+                "> Quoted\n>\n> Twice\n\n",
+                "![A picture](https://example.com/posts/pic.png)\n\n",
+                "Line\nbreak",
+            )
+        );
+    }
+
+    #[test]
+    fn test_html_to_markdown_without_article() {
+        let html = "<body><div class=\"menu\"><p>Menu</p></div><div><p>The long article text.</p><p>More.</p></div></body>";
+        let url = Url::parse("https://example.com/").unwrap();
+        assert_eq!(
+            html_to_markdown(html, &url),
+            "The long article text.\n\nMore."
+        );
+    }
+
+    #[test]
+    fn test_replace_snapshot() {
+        let section = "## Snapshot\n\nNew\n";
+        assert_eq!(
+            replace_snapshot("---\ntitle: A\n---\nMy notes\n", section),
+            "---\ntitle: A\n---\nMy notes\n\n## Snapshot\n\nNew\n"
+        );
+        assert_eq!(
+            replace_snapshot("---\ntitle: A\n---\n", section),
+            "---\ntitle: A\n---\n## Snapshot\n\nNew\n"
+        );
+        assert_eq!(
+            replace_snapshot(
+                "Notes\n\n## Snapshot\n\nOld\n\n### Old heading\n\n## Later\n",
+                section
+            ),
+            "Notes\n\n## Snapshot\n\nNew\n\n## Later\n"
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use crate::bookmarks::bookmark_urls;
+use crate::error::{succeeded, InkError};
+use crate::frontmatter::{update_tags, FrontMatterEditor};
+use crate::http::{check_url, checking_client, FetchError, UrlStatus};
+use crate::markdown::get_markdown_str;
+use crate::search::reindex_files;
+use crate::settings::SETTINGS;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// The tag added to bookmarks whose page is gone.
+pub const DEAD_LINK_TAG: &str = "dead-link";
+
+/// Client errors that can clear up on their own, so the page is not gone.
+const TEMPORARY_CLIENT_ERRORS: [u16; 3] = [408, 425, 429];
+
+/// Whether an error status means the page is gone for good, like a 404 or
+/// 410, rather than a server error or being rate limited.
+fn is_permanent_error(code: u16) -> bool {
+    (400..500).contains(&code) && !TEMPORARY_CLIENT_ERRORS.contains(&code)
+}
+
+/// Options for `ink mark check`.
+pub struct CheckOptions {
+    /// Defaults to the `[http]` timeout
+    pub timeout: Option<Duration>,
+    pub concurrency: usize,
+    pub tag_dead: bool,
+    pub fix_redirects: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Ok,
+    Moved,
+    Broken,
+    Dns,
+    Error,
+}
+
+#[derive(Debug, Serialize)]
+struct BookmarkCheck {
+    path: PathBuf,
+    url: String,
+    status: CheckStatus,
+    code: Option<u16>,
+    /// Where a permanent redirect points
+    location: Option<String>,
+    error: Option<String>,
+    /// Whether the note was tagged or its url rewritten
+    updated: bool,
+}
+
+impl BookmarkCheck {
+    fn new(path: PathBuf, url: String, url_status: UrlStatus) -> Self {
+        let (status, code, location, error) = match url_status {
+            UrlStatus::Ok(code) => (CheckStatus::Ok, Some(code), None, None),
+            UrlStatus::Moved { code, location } => {
+                (CheckStatus::Moved, Some(code), Some(location), None)
+            }
+            UrlStatus::Broken(code) if is_permanent_error(code) => {
+                (CheckStatus::Broken, Some(code), None, None)
+            }
+            UrlStatus::Broken(code) => (
+                CheckStatus::Error,
+                Some(code),
+                None,
+                Some(format!("the server responded with {code}")),
+            ),
+            UrlStatus::DnsFailure(error) => (CheckStatus::Dns, None, None, Some(error)),
+            UrlStatus::Failed(error) => (CheckStatus::Error, None, None, Some(error)),
+        };
+        BookmarkCheck {
+            path,
+            url,
+            status,
+            code,
+            location,
+            error,
+            updated: false,
+        }
+    }
+
+    /// Dead bookmarks are gone for good: permanent client errors and hosts
+    /// that no longer resolve.
+    fn is_dead(&self) -> bool {
+        matches!(self.status, CheckStatus::Broken | CheckStatus::Dns)
+    }
+}
+
+/// Check every url on `concurrency` threads, keeping the order of `bookmarks`.
+fn check_all(
+    bookmarks: Vec<(PathBuf, String)>,
+    options: &CheckOptions,
+) -> Result<Vec<BookmarkCheck>, FetchError> {
+    let timeout = options
+        .timeout
+        .unwrap_or(Duration::from_secs(SETTINGS.http.timeout));
+    let client = checking_client()?;
+    let next = AtomicUsize::new(0);
+    let statuses: Mutex<Vec<Option<UrlStatus>>> = Mutex::new(vec![None; bookmarks.len()]);
+    std::thread::scope(|scope| {
+        for _ in 0..options.concurrency.clamp(1, bookmarks.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some((_, url)) = bookmarks.get(index) else {
+                    break;
+                };
+                let status = check_url(client, url, timeout);
+                statuses.lock().unwrap()[index] = Some(status);
+            });
+        }
+    });
+    let statuses = statuses.into_inner().unwrap();
+    Ok(bookmarks
+        .into_iter()
+        .zip(statuses)
+        .map(|((path, url), status)| {
+            let status = status.unwrap_or_else(|| UrlStatus::Failed(String::from("not checked")));
+            BookmarkCheck::new(path, url, status)
+        })
+        .collect())
+}
+
+/// Tag a dead bookmark or point it at its new url. Returns whether the note changed.
+fn update_bookmark(check: &BookmarkCheck, options: &CheckOptions) -> Result<bool, InkError> {
+    let raw_markdown = get_markdown_str(&check.path)?;
+    let mut updated = raw_markdown.clone();
+    if options.tag_dead && check.is_dead() {
+        updated = update_tags(&updated, SETTINGS.frontmatter_format, |mut tags| {
+            if !tags.iter().any(|tag| tag == DEAD_LINK_TAG) {
+                tags.push(DEAD_LINK_TAG.to_string());
+            }
+            tags
+        });
+    }
+    if let (true, Some(location)) = (options.fix_redirects, &check.location) {
+        let mut editor = FrontMatterEditor::new(&updated);
+        editor.set("url", location.as_str());
+        updated = editor.into_string();
+    }
+    if updated == raw_markdown {
+        return Ok(false);
+    }
+    fs::write(&check.path, updated).map_err(InkError::io(&check.path))?;
+    Ok(true)
+}
+
+fn print_check(check: &BookmarkCheck) {
+    let path = check.path.display();
+    let url = &check.url;
+    match check.status {
+        CheckStatus::Ok => {}
+        CheckStatus::Moved => println!(
+            "moved\t{path}\t{url} -> {}",
+            check.location.as_deref().unwrap_or_default()
+        ),
+        CheckStatus::Broken => println!("{}\t{path}\t{url}", check.code.unwrap_or_default()),
+        CheckStatus::Dns => println!("dns\t{path}\t{url}"),
+        CheckStatus::Error => println!(
+            "error\t{path}\t{url}\t{}",
+            check.error.as_deref().unwrap_or_default()
+        ),
+    }
+}
+
+/// Check that the url of every bookmark still works, reporting pages that are
+/// gone, unresolvable hosts, server errors and permanent redirects.
+///
+/// Fails when any bookmark is dead.
+pub fn check_bookmarks(options: &CheckOptions, is_json: bool) -> Result<(), InkError> {
+    let mut checks = check_all(bookmark_urls()?, options)?;
+
+    let mut changed = Vec::new();
+    for check in &mut checks {
+        match update_bookmark(check, options) {
+            Ok(true) => {
+                eprintln!("Updated {}", check.path.display());
+                check.updated = true;
+                changed.push(check.path.clone());
+            }
+            Ok(false) => {}
+            Err(e) => eprintln!("Could not update {e}"),
+        }
+    }
+    if !changed.is_empty() {
+        if let Err(e) = reindex_files(&changed) {
+            eprintln!("Could not update the index {e}");
+        }
+    }
+
+    let dead = checks.iter().filter(|check| check.is_dead()).count();
+    if is_json {
+        println!("{}", serde_json::to_string(&checks).unwrap());
+        return succeeded(dead == 0);
+    }
+    for check in &checks {
+        print_check(check);
+    }
+    let count = |status: CheckStatus| checks.iter().filter(|check| check.status == status).count();
+    eprintln!(
+        "{} bookmarks checked, {dead} dead, {} moved, {} errors",
+        checks.len(),
+        count(CheckStatus::Moved),
+        count(CheckStatus::Error)
+    );
+    succeeded(dead == 0)
+}
@@ -0,0 +1,176 @@
+use crate::archive::split_snapshot;
+use crate::bookmarks::{bookmark_urls, normalize_url};
+use crate::error::{succeeded, InkError};
+use crate::frontmatter::{read_tags, update_tags, FrontMatterEditor};
+use crate::markdown::get_markdown_str;
+use crate::note::Note;
+use crate::search::{reindex_files, remove_files};
+use crate::settings::SETTINGS;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Frontmatter keys copied from a duplicate when the kept bookmark lacks them.
+const MERGED_KEYS: [&str; 7] = [
+    "description",
+    "site_name",
+    "author",
+    "published",
+    "canonical_url",
+    "favicon_url",
+    "archived",
+];
+
+/// Groups of bookmarks that point to the same page, the oldest one first.
+fn duplicate_groups() -> Result<Vec<Vec<PathBuf>>, InkError> {
+    let mut groups: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for (path, url) in bookmark_urls()? {
+        groups.entry(normalize_url(&url)).or_default().push(path);
+    }
+    let mut sorted_groups = Vec::new();
+    for paths in groups.into_values().filter(|paths| paths.len() > 1) {
+        let mut dated: Vec<(Option<DateTime<Utc>>, PathBuf)> = Vec::new();
+        for path in paths {
+            let created = Note::from_markdown_file(&path)?.created;
+            dated.push((created, path));
+        }
+        dated.sort_by(|(a, a_path), (b, b_path)| {
+            (a.is_none(), a, a_path).cmp(&(b.is_none(), b, b_path))
+        });
+        sorted_groups.push(dated.into_iter().map(|(_, path)| path).collect());
+    }
+    sorted_groups.sort();
+    Ok(sorted_groups)
+}
+
+/// Merge duplicate bookmarks into `kept`, combining their tags, filling in
+/// missing page metadata and adding any text of theirs that `kept` does not
+/// already contain. A snapshot is carried over only when `kept` has none.
+fn merge_bookmarks(kept: &str, duplicates: &[String]) -> String {
+    let mut editor = FrontMatterEditor::new(kept);
+    let (notes, snapshot) = split_snapshot(editor.body());
+    let mut snapshot = snapshot.map(str::to_string);
+    let mut sections: Vec<String> = Vec::new();
+    if !notes.trim().is_empty() {
+        sections.push(notes.trim().to_string());
+    }
+    let mut tags = read_tags(kept);
+
+    for duplicate in duplicates {
+        let duplicate_editor = FrontMatterEditor::new(duplicate);
+        for key in MERGED_KEYS {
+            if editor.get_str(key).is_none() {
+                if let Some(value) = duplicate_editor.get_str(key) {
+                    editor.set(key, value);
+                }
+            }
+        }
+        for tag in read_tags(duplicate) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        let (notes, duplicate_snapshot) = split_snapshot(duplicate_editor.body());
+        let notes = notes.trim();
+        if !notes.is_empty() && !sections.iter().any(|section| section.contains(notes)) {
+            sections.push(notes.to_string());
+        }
+        if snapshot.is_none() {
+            snapshot = duplicate_snapshot.map(str::to_string);
+        }
+    }
+    sections.extend(snapshot);
+
+    let frontmatter_end = editor.as_str().len() - editor.body().len();
+    let mut merged = editor.as_str()[..frontmatter_end].to_string();
+    if !sections.is_empty() {
+        merged.push_str(&sections.join("\n\n"));
+        merged.push('\n');
+    }
+    update_tags(&merged, SETTINGS.frontmatter_format, |_| tags)
+}
+
+fn merge_group(kept: &Path, duplicates: &[PathBuf]) -> Result<(), InkError> {
+    let duplicate_contents = duplicates
+        .iter()
+        .map(|path| get_markdown_str(path))
+        .collect::<Result<Vec<String>, InkError>>()?;
+    let merged = merge_bookmarks(&get_markdown_str(kept)?, &duplicate_contents);
+    fs::write(kept, merged).map_err(InkError::io(kept))?;
+    for duplicate in duplicates {
+        fs::remove_file(duplicate).map_err(InkError::io(duplicate))?;
+    }
+    Ok(())
+}
+
+/// Merge bookmarks that point to the same page into the oldest of them.
+/// With `dry_run`, only list what would be merged.
+pub fn dedupe(dry_run: bool) -> Result<(), InkError> {
+    let groups = duplicate_groups()?;
+    if groups.is_empty() {
+        eprintln!("No duplicate bookmarks found");
+        return Ok(());
+    }
+
+    let mut kept_paths = Vec::new();
+    let mut removed_paths = Vec::new();
+    let mut ok = true;
+    for group in groups {
+        let (kept, duplicates) = group.split_first().unwrap();
+        let verb = if dry_run { "Would merge" } else { "Merged" };
+        for duplicate in duplicates {
+            println!("{verb} {} into {}", duplicate.display(), kept.display());
+        }
+        if dry_run {
+            continue;
+        }
+        match merge_group(kept, duplicates) {
+            Ok(()) => {
+                kept_paths.push(kept.clone());
+                removed_paths.extend(duplicates.iter().cloned());
+            }
+            Err(e) => {
+                eprintln!("Could not merge into {}, {e}", kept.display());
+                ok = false;
+            }
+        }
+    }
+
+    if !dry_run {
+        let updated = remove_files(&removed_paths).and_then(|()| reindex_files(&kept_paths));
+        if let Err(e) = updated {
+            eprintln!("Could not update the index {e}");
+        }
+    }
+    succeeded(ok)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_bookmarks() {
+        let kept = "---\ntitle: Post\nurl: https://example.com/post\ntags:\n- bookmark\n- rust\n---\nRead this first\n";
+        let duplicate = "---\ntitle: Post\nurl: http://www.example.com/post/\ntags:\n- bookmark\n- async\ndescription: \"A post\"\n---\nGood on lifetimes\n";
+        assert_eq!(
+            merge_bookmarks(kept, &[duplicate.to_string()]),
+            "---\ntitle: Post\nurl: https://example.com/post\ntags:\n- bookmark\n- rust\n- async\ndescription: A post\n---\nRead this first\n\nGood on lifetimes\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_bookmarks_keeps_one_snapshot() {
+        let kept = "---\nurl: https://example.com\n---\nMine\n\n## Snapshot\n\nKept text\n";
+        let duplicate = "---\nurl: https://example.com\n---\nMine\n\n## Snapshot\n\nOther text\n";
+        assert_eq!(merge_bookmarks(kept, &[duplicate.to_string()]), kept);
+
+        let kept = "---\nurl: https://example.com\n---\n";
+        let duplicate = "---\nurl: https://example.com\narchived: 2026-10-01\n---\nTheirs\n\n## Snapshot\n\nText\n";
+        assert_eq!(
+            merge_bookmarks(kept, &[duplicate.to_string()]),
+            "---\nurl: https://example.com\narchived: 2026-10-01\n---\nTheirs\n\n## Snapshot\n\nText\n"
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use crate::archive::split_snapshot;
+use crate::bookmarks::bookmark_urls;
+use crate::cli::BookmarkFormat;
+use crate::error::InkError;
+use crate::links::normalize_path;
+use crate::note::Note;
+use crate::search::find_notes;
+use crate::utils::escape_xml;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::json;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+const EXPORT_TITLE: &str = "Bookmarks";
+
+/// The parts of a bookmark other tools understand.
+#[derive(Debug)]
+struct ExportedBookmark {
+    url: String,
+    title: String,
+    description: Option<String>,
+    tags: Vec<String>,
+    created: Option<DateTime<Utc>>,
+}
+
+impl ExportedBookmark {
+    /// The text written in the note, or the description of the page when there is none.
+    fn from_note(note: Note) -> Option<Self> {
+        let url = note.url?;
+        let (notes, _) = split_snapshot(note.body.as_deref().unwrap_or_default());
+        let description = Some(notes.trim().to_string())
+            .filter(|notes| !notes.is_empty())
+            .or(note.metadata.description);
+        let mut tags: Vec<String> = note.tags.into_iter().collect();
+        tags.sort();
+        Some(ExportedBookmark {
+            url,
+            title: note.title,
+            description,
+            tags,
+            created: note.created,
+        })
+    }
+}
+
+fn to_netscape(bookmarks: &[ExportedBookmark]) -> String {
+    let mut output = format!(
+        "<!DOCTYPE NETSCAPE-Bookmark-file-1>\n\
+         <META HTTP-EQUIV=\"Content-Type\" CONTENT=\"text/html; charset=UTF-8\">\n\
+         <TITLE>{EXPORT_TITLE}</TITLE>\n\
+         <H1>{EXPORT_TITLE}</H1>\n\
+         <DL><p>\n"
+    );
+    for bookmark in bookmarks {
+        let _ = write!(output, "    <DT><A HREF=\"{}\"", escape_xml(&bookmark.url));
+        if let Some(created) = bookmark.created {
+            let _ = write!(output, " ADD_DATE=\"{}\"", created.timestamp());
+        }
+        if !bookmark.tags.is_empty() {
+            let _ = write!(output, " TAGS=\"{}\"", escape_xml(&bookmark.tags.join(",")));
+        }
+        let _ = writeln!(output, ">{}</A>", escape_xml(&bookmark.title));
+        if let Some(description) = &bookmark.description {
+            let _ = writeln!(output, "    <DD>{}", escape_xml(description));
+        }
+    }
+    output.push_str("</DL><p>\n");
+    output
+}
+
+fn to_json_feed(bookmarks: &[ExportedBookmark]) -> String {
+    let items: Vec<_> = bookmarks
+        .iter()
+        .map(|bookmark| {
+            let mut item = json!({
+                "id": bookmark.url,
+                "url": bookmark.url,
+                "title": bookmark.title,
+                "content_text": bookmark.description.as_deref().unwrap_or_default(),
+                "tags": bookmark.tags,
+            });
+            if let Some(created) = bookmark.created {
+                item["date_published"] = json!(created.to_rfc3339_opts(SecondsFormat::Secs, true));
+            }
+            item
+        })
+        .collect();
+    let feed = json!({
+        "version": "https://jsonfeed.org/version/1.1",
+        "title": EXPORT_TITLE,
+        "items": items,
+    });
+    format!("{}\n", serde_json::to_string_pretty(&feed).unwrap())
+}
+
+fn to_opml(bookmarks: &[ExportedBookmark]) -> String {
+    let mut output = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <opml version=\"2.0\">\n  \
+         <head>\n    <title>{EXPORT_TITLE}</title>\n  </head>\n  \
+         <body>\n"
+    );
+    for bookmark in bookmarks {
+        let _ = write!(
+            output,
+            "    <outline type=\"link\" text=\"{}\" url=\"{}\"",
+            escape_xml(&bookmark.title),
+            escape_xml(&bookmark.url)
+        );
+        if let Some(created) = bookmark.created {
+            let _ = write!(output, " created=\"{}\"", created.to_rfc2822());
+        }
+        if !bookmark.tags.is_empty() {
+            let _ = write!(
+                output,
+                " category=\"{}\"",
+                escape_xml(&bookmark.tags.join(","))
+            );
+        }
+        if let Some(description) = &bookmark.description {
+            let _ = write!(output, " description=\"{}\"", escape_xml(description));
+        }
+        output.push_str("/>\n");
+    }
+    output.push_str("  </body>\n</opml>\n");
+    output
+}
+
+/// Quote a CSV field when it holds a separator, quote or line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn to_csv(bookmarks: &[ExportedBookmark]) -> String {
+    let mut output = String::from("url,title,description,tags,created\n");
+    for bookmark in bookmarks {
+        let fields = [
+            bookmark.url.clone(),
+            bookmark.title.clone(),
+            bookmark.description.clone().unwrap_or_default(),
+            bookmark.tags.join(","),
+            bookmark
+                .created
+                .map(|created| created.to_rfc3339_opts(SecondsFormat::Secs, true))
+                .unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        let _ = writeln!(output, "{}", row.join(","));
+    }
+    output
+}
+
+/// Print bookmarks in a format browsers, feed readers or spreadsheets can load,
+/// limited to those with one of `tags` and matching `query` when given.
+pub fn export_bookmarks(
+    format: BookmarkFormat,
+    tags: &[String],
+    query: Option<&str>,
+) -> Result<(), InkError> {
+    let matching_paths: Option<HashSet<PathBuf>> = match query {
+        Some(query) => Some(
+            find_notes(query, None, usize::MAX, true)?
+                .iter()
+                .map(|note| normalize_path(&note.get_file_path()))
+                .collect(),
+        ),
+        None => None,
+    };
+    let mut bookmarks: Vec<ExportedBookmark> = Vec::new();
+    for (path, _) in bookmark_urls()? {
+        if matching_paths
+            .as_ref()
+            .is_some_and(|paths| !paths.contains(&normalize_path(&path)))
+        {
+            continue;
+        }
+        let note = Note::from_markdown_file(&path)?;
+        if tags.is_empty() || tags.iter().any(|tag| note.tags.contains(tag)) {
+            bookmarks.extend(ExportedBookmark::from_note(note));
+        }
+    }
+
+    let output = match format {
+        BookmarkFormat::Netscape => to_netscape(&bookmarks),
+        BookmarkFormat::Jsonfeed => to_json_feed(&bookmarks),
+        BookmarkFormat::Opml => to_opml(&bookmarks),
+        BookmarkFormat::Csv => to_csv(&bookmarks),
+    };
+    print!("{output}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmarks() -> Vec<ExportedBookmark> {
+        vec![
+            ExportedBookmark {
+                url: String::from("https://example.com/?a=1&b=2"),
+                title: String::from("Tips & \"tricks\""),
+                description: Some(String::from("Short, sweet")),
+                tags: vec![String::from("bookmark"), String::from("rust")],
+                created: DateTime::from_timestamp(1_700_000_000, 0),
+            },
+            ExportedBookmark {
+                url: String::from("https://example.org"),
+                title: String::from("Plain"),
+                description: None,
+                tags: vec![],
+                created: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_to_netscape() {
+        let output = to_netscape(&bookmarks());
+        assert!(output.starts_with("<!DOCTYPE NETSCAPE-Bookmark-file-1>\n"));
+        assert!(output.contains(concat!(
+            "    <DT><A HREF=\"https://example.com/?a=1&amp;b=2\" ADD_DATE=\"1700000000\" TAGS=\"bookmark,rust\">Tips &amp; &quot;tricks&quot;</A>\n",
+            "    <DD>Short, sweet\n",
+            "    <DT><A HREF=\"https://example.org\">Plain</A>\n",
+            "</DL><p>\n",
+        )));
+    }
+
+    #[test]
+    fn test_to_json_feed() {
+        let feed: serde_json::Value = serde_json::from_str(&to_json_feed(&bookmarks())).unwrap();
+        assert_eq!(feed["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(feed["items"][0]["title"], "Tips & \"tricks\"");
+        assert_eq!(feed["items"][0]["tags"], json!(["bookmark", "rust"]));
+        assert_eq!(feed["items"][0]["date_published"], "2023-11-14T22:13:20Z");
+        assert_eq!(feed["items"][1]["content_text"], "");
+        assert!(feed["items"][1].get("date_published").is_none());
+    }
+
+    #[test]
+    fn test_to_opml() {
+        let output = to_opml(&bookmarks());
+        assert!(output.contains(
+            "    <outline type=\"link\" text=\"Tips &amp; &quot;tricks&quot;\" url=\"https://example.com/?a=1&amp;b=2\" created=\"Tue, 14 Nov 2023 22:13:20 +0000\" category=\"bookmark,rust\" description=\"Short, sweet\"/>\n"
+        ));
+        assert!(output.ends_with("  </body>\n</opml>\n"));
+    }
+
+    #[test]
+    fn test_to_csv() {
+        assert_eq!(
+            to_csv(&bookmarks()),
+            concat!(
+                "url,title,description,tags,created\n",
+                "https://example.com/?a=1&b=2,\"Tips & \"\"tricks\"\"\",\"Short, sweet\",\"bookmark,rust\",2023-11-14T22:13:20Z\n",
+                "https://example.org,Plain,,,\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,78 @@
+//! Just enough RFC 4180 to read the CSV exports of bookmark services.
+use std::collections::HashMap;
+
+/// Split CSV text into rows of fields, honouring quoted fields with embedded
+/// separators, doubled quotes and line breaks.
+fn parse_rows(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(String::from("unterminated quoted field"));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// Read CSV with a header row into one map of column name to value per record.
+pub fn read_records(text: &str) -> Result<Vec<HashMap<String, String>>, String> {
+    let mut rows = parse_rows(text)?.into_iter();
+    let header: Vec<String> = rows
+        .next()
+        .ok_or("the file is empty")?
+        .into_iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    Ok(rows
+        .filter(|row| row.iter().any(|field| !field.is_empty()))
+        .map(|row| header.iter().cloned().zip(row).collect())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rows() {
+        assert_eq!(
+            parse_rows("a,b\r\n\"one, two\",\"say \"\"hi\"\"\nthere\"\n,last").unwrap(),
+            vec![
+                vec!["a", "b"],
+                vec!["one, two", "say \"hi\"\nthere"],
+                vec!["", "last"],
+            ]
+        );
+        assert!(parse_rows("a,\"open").is_err());
+    }
+
+    #[test]
+    fn test_read_records() {
+        let records = read_records("Title,URL\nRust,https://rust-lang.org\n\n").unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0]["title"], "Rust");
+        assert_eq!(records[0]["url"], "https://rust-lang.org");
+    }
+}
@@ -0,0 +1,137 @@
+mod csv;
+mod netscape;
+mod pinboard;
+mod pocket;
+mod raindrop;
+
+use crate::bookmarks::{bookmark_urls, normalize_url, unique_id};
+use crate::cli::ImportSource;
+use crate::error::InkError;
+use crate::note::Note;
+use crate::page::{fetch_page_metadata, PageMetadata};
+use crate::search::reindex_files;
+use crate::utils::slugify;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// A bookmark read from another tool's export.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportedBookmark {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// A summary of the page, as opposed to the notes written about it
+    pub excerpt: Option<String>,
+    pub tags: Vec<String>,
+    pub created: Option<DateTime<Utc>>,
+    /// Saved to read later and not read yet
+    pub unread: bool,
+}
+
+#[derive(Debug, Default)]
+struct ImportSummary {
+    created: usize,
+    duplicates: usize,
+    skipped: usize,
+}
+
+/// Turn a folder or tag name from an export into an ink tag.
+fn import_tag(name: &str) -> Option<String> {
+    let tag = slugify(name);
+    (!tag.is_empty()).then_some(tag)
+}
+
+fn create_note(bookmark: ImportedBookmark, fetch: bool) -> Result<Note, InkError> {
+    let mut metadata = if fetch {
+        fetch_page_metadata(&bookmark.url).unwrap_or_else(|e| {
+            eprintln!("Could not fetch {}: {e}", bookmark.url);
+            PageMetadata::default()
+        })
+    } else {
+        PageMetadata::default()
+    };
+    let is_present = |text: &String| !text.trim().is_empty();
+    let title = metadata
+        .title
+        .take()
+        .filter(is_present)
+        .or(bookmark.title.filter(is_present))
+        .unwrap_or_else(|| bookmark.url.clone());
+    let description = bookmark
+        .description
+        .filter(is_present)
+        .map(|description| format!("{}\n", description.trim()));
+    let mut note = Note::new_bookmark_with_title(
+        &bookmark.url,
+        title.clone(),
+        Some(unique_id(&title)),
+        description,
+    );
+    metadata.description = metadata.description.or(bookmark.excerpt.filter(is_present));
+    note.metadata = metadata;
+    note.created = bookmark.created;
+    for tag in bookmark.tags.iter().filter_map(|tag| import_tag(tag)) {
+        note.add_tag(tag);
+    }
+    if bookmark.unread {
+        note.read = Some(false);
+    }
+    note.render_new_note()?;
+    Ok(note)
+}
+
+/// Create a note for each bookmark that is not saved yet, fetching page
+/// titles and metadata when `fetch` is set.
+fn import(bookmarks: Vec<ImportedBookmark>, fetch: bool) -> Result<ImportSummary, InkError> {
+    let mut summary = ImportSummary::default();
+    let mut known: HashSet<String> = bookmark_urls()?
+        .iter()
+        .map(|(_, url)| normalize_url(url))
+        .collect();
+    let mut paths = Vec::new();
+    for bookmark in bookmarks {
+        if !matches!(bookmark.url.split_once(':'), Some(("http" | "https", _))) {
+            summary.skipped += 1;
+            continue;
+        }
+        if !known.insert(normalize_url(&bookmark.url)) {
+            summary.duplicates += 1;
+            continue;
+        }
+        let note = create_note(bookmark, fetch)?;
+        let path = note.get_file_path();
+        println!("{}", path.display());
+        paths.push(path);
+        summary.created += 1;
+    }
+    if !paths.is_empty() {
+        if let Err(e) = reindex_files(&paths) {
+            eprintln!("Could not update the index {e}");
+        }
+    }
+    Ok(summary)
+}
+
+fn parse(source: ImportSource, text: &str) -> Result<Vec<ImportedBookmark>, String> {
+    match source {
+        ImportSource::Netscape => Ok(netscape::parse(text)),
+        ImportSource::Pinboard => pinboard::parse(text).map_err(|e| e.to_string()),
+        ImportSource::Pocket => pocket::parse(text),
+        ImportSource::Raindrop => raindrop::parse(text),
+    }
+}
+
+/// Import the bookmarks in a browser's HTML export or a bookmark service's
+/// export. Pages are only fetched for browser exports, and only with `fetch`.
+pub fn import_bookmarks(path: &Path, source: ImportSource, fetch: bool) -> Result<(), InkError> {
+    let text = fs::read_to_string(path).map_err(InkError::io(path))?;
+    let bookmarks = parse(source, &text).map_err(|e| InkError::parse(path, e))?;
+    let summary = import(bookmarks, fetch && source == ImportSource::Netscape)?;
+    eprintln!(
+        "{} created, {} duplicates, {} skipped",
+        summary.created, summary.duplicates, summary.skipped
+    );
+    Ok(())
+}
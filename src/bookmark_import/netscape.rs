@@ -0,0 +1,151 @@
+//! The Netscape bookmark file format, as exported by every major browser.
+//!
+//! It is HTML in name only: tags are left unclosed and nesting is implied by
+//! `<DL>` lists, so the file is scanned as a stream of the few tags that matter
+//! rather than parsed as a document.
+use super::ImportedBookmark;
+use chrono::DateTime;
+use regex::Regex;
+use scraper::Html;
+use std::sync::LazyLock;
+
+static TOKEN_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?is)<h3([^>]*)>(.*?)</h3>|<dl[^>]*>|</dl>|<a\s([^>]*)>(.*?)</a>|<dd>([^<]*)")
+        .unwrap()
+});
+static ATTRIBUTE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([A-Za-z_-]+)\s*=\s*"([^"]*)""#).unwrap());
+
+/// Folders browsers add themselves, which say nothing about the bookmarks in them.
+const BROWSER_FOLDER_ATTRIBUTES: [&str; 2] =
+    ["personal_toolbar_folder", "unfiled_bookmarks_folder"];
+
+/// Decode the entities in a fragment of HTML and collapse its whitespace.
+fn decode(text: &str) -> String {
+    let fragment = Html::parse_fragment(text);
+    let text: String = fragment.root_element().text().collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    ATTRIBUTE_RE
+        .captures_iter(attributes)
+        .find(|captures| captures[1].eq_ignore_ascii_case(name))
+        .map(|captures| decode(&captures[2]))
+}
+
+fn has_attribute(attributes: &str, name: &str) -> bool {
+    ATTRIBUTE_RE
+        .captures_iter(attributes)
+        .any(|captures| captures[1].eq_ignore_ascii_case(name))
+}
+
+/// `ADD_DATE` is in seconds since the epoch.
+fn parse_timestamp(timestamp: &str) -> Option<DateTime<chrono::Utc>> {
+    DateTime::from_timestamp(timestamp.trim().parse().ok()?, 0)
+}
+
+/// Read every bookmark, tagged with the folders it is in and its own `TAGS`.
+pub fn parse(html: &str) -> Vec<ImportedBookmark> {
+    let mut bookmarks: Vec<ImportedBookmark> = Vec::new();
+    // The folder of each open `<DL>`, `None` for the root and browser folders
+    let mut folders: Vec<Option<String>> = Vec::new();
+    let mut pending_folder: Option<String> = None;
+
+    for captures in TOKEN_RE.captures_iter(html) {
+        let token = &captures[0];
+        if let (Some(attributes), Some(name)) = (captures.get(1), captures.get(2)) {
+            let is_browser_folder = BROWSER_FOLDER_ATTRIBUTES
+                .iter()
+                .any(|attribute_name| has_attribute(attributes.as_str(), attribute_name));
+            pending_folder = (!is_browser_folder).then(|| decode(name.as_str()));
+        } else if let (Some(attributes), Some(title)) = (captures.get(3), captures.get(4)) {
+            let attributes = attributes.as_str();
+            let Some(url) = attribute(attributes, "href") else {
+                continue;
+            };
+            let mut tags: Vec<String> = folders.iter().flatten().cloned().collect();
+            if let Some(own_tags) = attribute(attributes, "tags") {
+                tags.extend(own_tags.split(',').map(|tag| tag.trim().to_string()));
+            }
+            let title = decode(title.as_str());
+            bookmarks.push(ImportedBookmark {
+                url,
+                title: (!title.is_empty()).then_some(title),
+                tags,
+                created: attribute(attributes, "add_date")
+                    .as_deref()
+                    .and_then(parse_timestamp),
+                ..ImportedBookmark::default()
+            });
+        } else if let Some(description) = captures.get(5) {
+            if let Some(bookmark) = bookmarks.last_mut() {
+                let description = decode(description.as_str());
+                bookmark.description = (!description.is_empty()).then_some(description);
+            }
+        } else if token.starts_with("</") {
+            folders.pop();
+        } else {
+            folders.push(pending_folder.take());
+        }
+    }
+    bookmarks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<META HTTP-EQUIV="Content-Type" CONTENT="text/html; charset=UTF-8">
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1600000000" PERSONAL_TOOLBAR_FOLDER="true">Bookmarks bar</H3>
+    <DL><p>
+        <DT><A HREF="https://www.rust-lang.org/" ADD_DATE="1700000000">Rust &amp; Cargo</A>
+        <DT><H3>Reading List</H3>
+        <DL><p>
+            <DT><A HREF="https://example.com/post?a=1&amp;b=2" ADD_DATE="1710000000" TAGS="async,tokio">A post</A>
+            <DD>Worth a second read
+        </DL><p>
+    </DL><p>
+    <DT><A HREF="javascript:alert(1)">Bookmarklet</A>
+</DL><p>
+"#;
+
+    #[test]
+    fn test_parse() {
+        let bookmarks = parse(EXPORT);
+        assert_eq!(
+            bookmarks,
+            vec![
+                ImportedBookmark {
+                    url: String::from("https://www.rust-lang.org/"),
+                    title: Some(String::from("Rust & Cargo")),
+                    description: None,
+                    tags: vec![],
+                    created: DateTime::from_timestamp(1_700_000_000, 0),
+                    ..Default::default()
+                },
+                ImportedBookmark {
+                    url: String::from("https://example.com/post?a=1&b=2"),
+                    title: Some(String::from("A post")),
+                    description: Some(String::from("Worth a second read")),
+                    tags: vec![
+                        String::from("Reading List"),
+                        String::from("async"),
+                        String::from("tokio")
+                    ],
+                    created: DateTime::from_timestamp(1_710_000_000, 0),
+                    ..Default::default()
+                },
+                ImportedBookmark {
+                    url: String::from("javascript:alert(1)"),
+                    title: Some(String::from("Bookmarklet")),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,61 @@
+//! Pinboard's JSON export, from <https://pinboard.in/export/>.
+use super::ImportedBookmark;
+use crate::markdown::parse_date;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+struct Post {
+    href: String,
+    /// Pinboard calls the title the description
+    #[serde(default)]
+    description: String,
+    /// The notes written about the bookmark
+    #[serde(default)]
+    extended: String,
+    /// Space separated
+    #[serde(default)]
+    tags: String,
+    #[serde(default)]
+    time: String,
+    #[serde(default)]
+    toread: String,
+}
+
+pub fn parse(json: &str) -> serde_json::Result<Vec<ImportedBookmark>> {
+    let posts: Vec<Post> = serde_json::from_str(json)?;
+    Ok(posts
+        .into_iter()
+        .map(|post| ImportedBookmark {
+            url: post.href,
+            title: Some(post.description),
+            description: Some(post.extended),
+            tags: post.tags.split_whitespace().map(str::to_string).collect(),
+            created: parse_date(&post.time),
+            unread: post.toread == "yes",
+            ..ImportedBookmark::default()
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    #[test]
+    fn test_parse() {
+        let bookmarks = parse(r#"[{"href":"https://example.com/","description":"Example","extended":"My notes","meta":"abc","hash":"def","time":"2023-11-14T22:13:20Z","shared":"no","toread":"yes","tags":"rust async"}]"#).unwrap();
+        assert_eq!(
+            bookmarks,
+            vec![ImportedBookmark {
+                url: String::from("https://example.com/"),
+                title: Some(String::from("Example")),
+                description: Some(String::from("My notes")),
+                tags: vec![String::from("rust"), String::from("async")],
+                created: DateTime::from_timestamp(1_700_000_000, 0),
+                unread: true,
+                ..ImportedBookmark::default()
+            }]
+        );
+    }
+}
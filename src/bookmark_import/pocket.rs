@@ -0,0 +1,58 @@
+//! Pocket's CSV export, with `title,url,time_added,tags,status` columns.
+use super::csv::read_records;
+use super::ImportedBookmark;
+use chrono::DateTime;
+
+pub fn parse(text: &str) -> Result<Vec<ImportedBookmark>, String> {
+    Ok(read_records(text)?
+        .into_iter()
+        .map(|record| {
+            let field = |name: &str| record.get(name).cloned().unwrap_or_default();
+            ImportedBookmark {
+                url: field("url"),
+                title: Some(field("title")),
+                // Pipe separated
+                tags: field("tags")
+                    .split('|')
+                    .filter(|tag| !tag.trim().is_empty())
+                    .map(|tag| tag.trim().to_string())
+                    .collect(),
+                created: field("time_added")
+                    .trim()
+                    .parse()
+                    .ok()
+                    .and_then(|seconds| DateTime::from_timestamp(seconds, 0)),
+                unread: field("status") == "unread",
+                ..ImportedBookmark::default()
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let bookmarks = parse("title,url,time_added,cursor,tags,status\nAn article,https://example.com/a,1700000000,,rust|long read,unread\n,https://example.com/b,,,,archive\n").unwrap();
+        assert_eq!(
+            bookmarks,
+            vec![
+                ImportedBookmark {
+                    url: String::from("https://example.com/a"),
+                    title: Some(String::from("An article")),
+                    tags: vec![String::from("rust"), String::from("long read")],
+                    created: DateTime::from_timestamp(1_700_000_000, 0),
+                    unread: true,
+                    ..ImportedBookmark::default()
+                },
+                ImportedBookmark {
+                    url: String::from("https://example.com/b"),
+                    title: Some(String::new()),
+                    ..ImportedBookmark::default()
+                },
+            ]
+        );
+    }
+}
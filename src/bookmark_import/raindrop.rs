@@ -0,0 +1,61 @@
+//! Raindrop.io's CSV export, with `title,note,excerpt,url,folder,tags,created` columns.
+use super::csv::read_records;
+use super::ImportedBookmark;
+use crate::markdown::parse_date;
+
+/// Where Raindrop puts bookmarks that were never filed.
+const UNSORTED_FOLDER: &str = "Unsorted";
+
+pub fn parse(text: &str) -> Result<Vec<ImportedBookmark>, String> {
+    Ok(read_records(text)?
+        .into_iter()
+        .map(|record| {
+            let field = |name: &str| record.get(name).cloned().unwrap_or_default();
+            let folder = field("folder");
+            let tags = field("tags");
+            let tags = tags
+                .split(',')
+                .chain((folder != UNSORTED_FOLDER).then_some(folder.as_str()))
+                .filter(|tag| !tag.trim().is_empty())
+                .map(|tag| tag.trim().to_string())
+                .collect();
+            ImportedBookmark {
+                url: field("url"),
+                title: Some(field("title")),
+                description: Some(field("note")),
+                excerpt: Some(field("excerpt")),
+                tags,
+                created: parse_date(&field("created")),
+                ..ImportedBookmark::default()
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    #[test]
+    fn test_parse() {
+        let bookmarks = parse("id,title,note,excerpt,url,folder,tags,created,cover,highlights,favorite\n1,Example,Worth it,The page says,https://example.com,Reading,\"rust, web\",2023-11-14T22:13:20.000Z,,,false\n2,Other,,,https://example.org,Unsorted,,,,,\n").unwrap();
+        assert_eq!(
+            bookmarks[0],
+            ImportedBookmark {
+                url: String::from("https://example.com"),
+                title: Some(String::from("Example")),
+                description: Some(String::from("Worth it")),
+                excerpt: Some(String::from("The page says")),
+                tags: vec![
+                    String::from("rust"),
+                    String::from("web"),
+                    String::from("Reading")
+                ],
+                created: DateTime::from_timestamp(1_700_000_000, 0),
+                unread: false,
+            }
+        );
+        assert!(bookmarks[1].tags.is_empty());
+    }
+}
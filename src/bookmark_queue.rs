@@ -0,0 +1,138 @@
+use crate::bookmarks::bookmark_urls;
+use crate::error::InkError;
+use crate::frontmatter::FrontMatterEditor;
+use crate::links::normalize_path;
+use crate::markdown::get_markdown_str;
+use crate::note::Note;
+use crate::resolve::resolve_note;
+use crate::search::reindex_files;
+use crate::settings::SETTINGS;
+use chrono::{DateTime, Local};
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::BuildHasher;
+use std::path::Path;
+
+/// Set the read state of a note, stamping when it was read or clearing the stamp
+/// when it goes back in the queue.
+fn with_read_state(raw_markdown: &str, read: bool, now: DateTime<Local>) -> String {
+    let mut editor =
+        FrontMatterEditor::new(raw_markdown).with_default_format(SETTINGS.frontmatter_format);
+    editor.set("read", read);
+    if read {
+        editor.set("read_at", now.to_rfc3339());
+    } else {
+        editor.remove("read_at");
+    }
+    editor.into_string()
+}
+
+fn set_read_state(path: &Path, read: bool) -> Result<(), InkError> {
+    let raw_markdown = get_markdown_str(path)?;
+    if FrontMatterEditor::new(&raw_markdown)
+        .get_str("url")
+        .is_none()
+    {
+        return Err(InkError::parse(path, "not a bookmark, it has no url"));
+    }
+    fs::write(path, with_read_state(&raw_markdown, read, Local::now()))
+        .map_err(InkError::io(path))?;
+    if let Err(e) = reindex_files(&[normalize_path(path)]) {
+        eprintln!("Could not update the index {e}");
+    }
+    Ok(())
+}
+
+/// Put the bookmark at `path` in the read-later queue.
+pub fn queue_path(path: &Path) -> Result<(), InkError> {
+    set_read_state(path, false)
+}
+
+/// Bookmarks queued to read later and not read yet, the oldest first.
+fn unread_bookmarks() -> Result<Vec<Note>, InkError> {
+    let mut unread = Vec::new();
+    for (path, _) in bookmark_urls()? {
+        let note = Note::from_markdown_file(&path)?;
+        if note.read == Some(false) {
+            unread.push(note);
+        }
+    }
+    // bookmark_urls is sorted by path, which breaks ties between equal dates
+    unread.sort_by_key(|note| (note.created.is_none(), note.created));
+    Ok(unread)
+}
+
+fn print_bookmarks(bookmarks: &[Note], is_json: bool) {
+    if is_json {
+        println!("{}", serde_json::to_string(bookmarks).unwrap());
+        return;
+    }
+    for note in bookmarks {
+        println!(
+            "{}\t{}\t{}",
+            note.id,
+            note.title,
+            note.url.as_deref().unwrap_or_default()
+        );
+    }
+}
+
+/// List the unread bookmarks in the read-later queue, oldest first.
+pub fn queue(is_json: bool) -> Result<(), InkError> {
+    print_bookmarks(&unread_bookmarks()?, is_json);
+    Ok(())
+}
+
+/// Mark a bookmark as read.
+pub fn read(note: &str) -> Result<(), InkError> {
+    let path = resolve_note(note)?;
+    set_read_state(&path, true)?;
+    println!("Read {}", path.display());
+    Ok(())
+}
+
+/// Print one unread bookmark picked at random. Fails when the queue is empty.
+pub fn random(is_json: bool) -> Result<(), InkError> {
+    let unread = unread_bookmarks()?;
+    if unread.is_empty() {
+        eprintln!("No unread bookmarks in the queue");
+        return Err(InkError::Failed);
+    }
+    // Every RandomState is seeded differently, which is random enough to pick one
+    let seed = RandomState::new().hash_one(unread.len());
+    let index = usize::try_from(seed % unread.len() as u64).unwrap_or_default();
+    print_bookmarks(&unread[index..=index], is_json);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_read_state() {
+        let now = DateTime::parse_from_rfc3339("2026-03-01T09:30:00+00:00")
+            .unwrap()
+            .with_timezone(&Local);
+        let queued = with_read_state(
+            "---\ntitle: Post\nurl: https://example.com\n---\nBody\n",
+            false,
+            now,
+        );
+        assert_eq!(
+            queued,
+            "---\ntitle: Post\nurl: https://example.com\nread: false\n---\nBody\n"
+        );
+        let read = with_read_state(&queued, true, now);
+        assert!(read.contains("read: true\nread_at: "));
+        assert!(read.ends_with("---\nBody\n"));
+        assert_eq!(with_read_state(&read, false, now), queued);
+    }
+
+    #[test]
+    fn test_with_read_state_toml() {
+        let now = Local::now();
+        let read = with_read_state("+++\ntitle = \"Post\"\n+++\n", true, now);
+        assert!(read.starts_with("+++\ntitle = \"Post\"\nread = true\nread_at = \""));
+    }
+}
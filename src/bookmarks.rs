@@ -1,62 +1,185 @@
-use crate::markdown::get_markdown_str;
+use crate::archive::archive_path;
+use crate::bookmark_queue::queue_path;
+use crate::error::InkError;
+use crate::markdown::{frontmatter, get_markdown_str};
 use crate::note::Note;
 use crate::settings::SETTINGS;
+use crate::utils::slugify;
 use crate::walk::{has_extension, walk_files};
 
-use gray_matter::engine::YAML;
-use gray_matter::Matter;
-use serde::Deserialize;
-use std::cell::RefCell;
-use walkdir::DirEntry;
+use reqwest::Url;
+use std::path::PathBuf;
 
-#[allow(dead_code)]
-#[derive(Deserialize)]
-struct BookmarkFrontMatter {
-    url: String,
+/// Query parameters that only track where a visitor came from.
+const TRACKING_PARAMS: [&str; 11] = [
+    "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "mc_cid", "mc_eid",
+    "igshid", "_hsenc",
+];
+
+fn is_tracking_param(name: &str) -> bool {
+    name.starts_with("utm_") || TRACKING_PARAMS.contains(&name)
+}
+
+/// Reduce a URL to a key that is the same for every way of writing the same page:
+/// `http` and `https`, with or without `www.`, a trailing slash, tracking
+/// parameters or a fragment.
+pub fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    let Ok(parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        let mut parsed = parsed;
+        parsed.set_fragment(None);
+        return parsed.to_string();
+    }
+    let host = parsed.host_str().unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let port = parsed
+        .port()
+        .map(|port| format!(":{port}"))
+        .unwrap_or_default();
+    let path = parsed.path().trim_end_matches('/');
+    let mut params: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(name, _)| !is_tracking_param(name))
+        .map(|(name, value)| (name.into_owned(), value.into_owned()))
+        .collect();
+    params.sort();
+    let mut query_url = parsed.clone();
+    query_url.set_query(None);
+    if !params.is_empty() {
+        query_url.query_pairs_mut().extend_pairs(params);
+    }
+    let query = query_url
+        .query()
+        .map(|query| format!("?{query}"))
+        .unwrap_or_default();
+    format!("https://{host}{port}{path}{query}")
 }
 
-fn contains_url(entry: &DirEntry) -> bool {
-    let Some(path_str) = entry.path().to_str() else {
-        return false;
+/// The first free note id for `title`, adding `-2`, `-3`... on collisions.
+pub fn unique_id(title: &str) -> String {
+    let slug = match slugify(title) {
+        slug if slug.is_empty() => String::from("bookmark"),
+        slug => slug,
     };
-    let matter = Matter::<YAML>::new();
-    let raw_markdown = get_markdown_str(path_str);
-    let result = matter.parse::<BookmarkFrontMatter>(&raw_markdown);
-    result.is_ok()
+    let notes_path = SETTINGS.get_notes_path();
+    let mut id = slug.clone();
+    let mut counter = 1;
+    while notes_path.join(format!("{id}.md")).exists() {
+        counter += 1;
+        id = format!("{slug}-{counter}");
+    }
+    id
+}
+
+/// Every bookmark in the notes folder with its url, sorted by path.
+pub fn bookmark_urls() -> Result<Vec<(PathBuf, String)>, InkError> {
+    let mut bookmarks = Vec::new();
+    walk_files(&SETTINGS.get_notes_path(), true, has_extension, |path| {
+        if let Some(url) = frontmatter(&get_markdown_str(path)?).url {
+            bookmarks.push((path.to_path_buf(), url));
+        }
+        Ok(())
+    })?;
+    bookmarks.sort();
+    Ok(bookmarks)
 }
 
-pub fn mark(is_json: bool) {
-    let notes_dir = &SETTINGS.get_notes_path();
+/// The bookmark that already points to the same page as `url`.
+fn find_bookmark(url: &str) -> Result<Option<PathBuf>, InkError> {
+    let normalized = normalize_url(url);
+    Ok(bookmark_urls()?
+        .into_iter()
+        .find(|(_, existing)| normalize_url(existing) == normalized)
+        .map(|(path, _)| path))
+}
+
+pub fn mark(is_json: bool) -> Result<(), InkError> {
+    let mut bookmarks = Vec::new();
+    walk_files(&SETTINGS.get_notes_path(), true, has_extension, |path| {
+        let note = Note::from_markdown_file(path)?;
+        if note.url.is_some() {
+            bookmarks.push(note);
+        }
+        Ok(())
+    })?;
     if is_json {
-        let bookmarks = RefCell::new(Vec::new());
-        walk_files(
-            notes_dir,
-            true,
-            |note_file| has_extension(note_file) && contains_url(note_file),
-            |path_str| {
-                let bookmark = Note::from_markdown_file(path_str);
-                bookmarks.borrow_mut().push(bookmark);
-            },
-        );
         println!("{}", serde_json::to_string(&bookmarks).unwrap());
-        return;
+        return Ok(());
     }
-    walk_files(
-        notes_dir,
-        true,
-        |note_file| has_extension(note_file) && contains_url(note_file),
-        |path_str| {
-            let note = Note::from_markdown_file(path_str);
-            println!("{}\t{}", note.title, note.url.unwrap());
-        },
-    );
+    for note in bookmarks {
+        println!("{}\t{}", note.title, note.url.unwrap_or_default());
+    }
+    Ok(())
+}
+
+/// Create a bookmark note, snapshotting the page too when `archive` is set and
+/// queueing it to read later when `later` is set.
+/// A url that is already bookmarked is not saved twice.
+pub fn create_bookmark(
+    url: &str,
+    description: Option<String>,
+    archive: bool,
+    later: bool,
+) -> Result<(), InkError> {
+    let path = if let Some(existing) = find_bookmark(url)? {
+        eprintln!("{url} is already bookmarked in {}", existing.display());
+        existing
+    } else {
+        let mut note = Note::new_bookmark(url, None, description)?;
+        println!("{}", note.title);
+
+        if note.file_exists() {
+            // Another note has this title, so the bookmark takes the next free id
+            note.set_id(unique_id(&note.title));
+        }
+        note.render_new_note()?;
+        note.get_file_path()
+    };
+    if later {
+        queue_path(&path)?;
+    }
+    if archive {
+        archive_path(&path)?;
+    }
+    Ok(())
 }
 
-pub fn create_bookmark(url: &str, description: Option<String>) {
-    let note = Note::new_bookmark(url, None, description);
-    println!("{}", note.title);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url() {
+        let normalized = "https://example.com/post";
+        for url in [
+            "https://example.com/post",
+            "http://www.example.com/post/",
+            "https://example.com/post?utm_source=x&utm_medium=email",
+            "https://EXAMPLE.com/post#comments",
+            "https://example.com:443/post?fbclid=abc",
+        ] {
+            assert_eq!(normalize_url(url), normalized, "{url}");
+        }
+    }
 
-    if !note.file_exists() {
-        note.render_new_note();
+    #[test]
+    fn test_normalize_url_keeps_meaningful_parts() {
+        assert_eq!(
+            normalize_url("https://example.com/search?q=rust&page=2&utm_campaign=x"),
+            "https://example.com/search?page=2&q=rust"
+        );
+        assert_eq!(normalize_url("https://example.com/"), "https://example.com");
+        assert_eq!(
+            normalize_url("http://localhost:8080/Docs/"),
+            "https://localhost:8080/Docs"
+        );
+        assert_ne!(
+            normalize_url("https://example.com/post"),
+            normalize_url("https://example.org/post")
+        );
+        assert_eq!(normalize_url("not a url "), "not a url");
     }
 }
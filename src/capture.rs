@@ -0,0 +1,183 @@
+use crate::error::InkError;
+use crate::frontmatter::FrontMatterEditor;
+use crate::links::normalize_path;
+use crate::markdown::{find_section, get_markdown_str};
+use crate::note::Note;
+use crate::prompt::ParsedQuery;
+use crate::resolve::resolve_note;
+use crate::search::reindex_files;
+use chrono::Local;
+use std::fmt::Write;
+use std::fs;
+use std::io::{IsTerminal, Read};
+use std::path::Path;
+
+const ENTRY_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+fn read_stdin() -> Result<String, InkError> {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .map_err(InkError::io(Path::new("stdin")))?;
+    Ok(input)
+}
+
+fn reindex(path: &Path) {
+    if let Err(e) = reindex_files(&[normalize_path(path)]) {
+        eprintln!("Could not update the index {e}");
+    }
+}
+
+/// Create a note titled and tagged by `query`, with stdin as its body.
+pub fn capture(query: &str) -> Result<(), InkError> {
+    if std::io::stdin().is_terminal() {
+        eprintln!("Reading the note body from stdin, end it with Ctrl-D");
+    }
+    let body = read_stdin()?;
+
+    let mut note = Note::from_parsed_prompt(ParsedQuery::from_query(query));
+    if note.file_exists() {
+        eprintln!(
+            "{} already exists, use `ink append` to add to it",
+            note.get_file_path().display()
+        );
+        return Err(InkError::Failed);
+    }
+    let body = body.trim_end();
+    if !body.is_empty() {
+        note.body = Some(format!("{body}\n"));
+    }
+    note.render_new_note()?;
+
+    let path = note.get_file_path();
+    println!("{}", path.display());
+    reindex(&path);
+    Ok(())
+}
+
+/// A bullet with a timestamp, continuation lines indented under it.
+fn format_entry(text: &str, timestamp: &str) -> String {
+    let mut lines = text.trim().lines();
+    let mut entry = format!("- {timestamp} {}\n", lines.next().unwrap_or_default());
+    for line in lines {
+        if line.trim().is_empty() {
+            entry.push('\n');
+        } else {
+            let _ = writeln!(entry, "  {line}");
+        }
+    }
+    entry
+}
+
+/// Whether the text ends with a list item, so a new entry joins that list.
+fn ends_with_list_item(text: &str) -> bool {
+    text.lines().last().is_some_and(|line| {
+        let line = line.trim_start();
+        line.starts_with("- ") || line.starts_with("* ")
+    })
+}
+
+/// Insert `entry` at the end of the section under `heading`, or at the end of
+/// the note. A missing heading is added at the end of the note.
+fn insert_entry(raw_markdown: &str, entry: &str, heading: Option<&str>) -> String {
+    let body_start = raw_markdown.len() - FrontMatterEditor::new(raw_markdown).body().len();
+    let section_end = heading
+        .and_then(|heading| find_section(&raw_markdown[body_start..], heading))
+        .map(|section| section.end);
+    let (before, after) =
+        raw_markdown.split_at(section_end.map_or(raw_markdown.len(), |end| body_start + end));
+    let before = before.trim_end();
+
+    let mut updated = before.to_string();
+    match (heading, section_end) {
+        (Some(heading), None) => {
+            if !before.is_empty() {
+                updated.push_str("\n\n");
+            }
+            let _ = write!(updated, "## {heading}\n\n");
+        }
+        _ if before.is_empty() => {}
+        _ if ends_with_list_item(before) => updated.push('\n'),
+        _ => updated.push_str("\n\n"),
+    }
+    updated.push_str(entry);
+    if !after.is_empty() {
+        updated.push('\n');
+        updated.push_str(after);
+    }
+    updated
+}
+
+/// Add a timestamped entry to an existing note, under `heading` if given.
+pub fn append(query: &str, text: Option<&str>, heading: Option<&str>) -> Result<(), InkError> {
+    let path = resolve_note(query)?;
+    let text = match text {
+        Some(text) => text.to_string(),
+        None => read_stdin()?,
+    };
+    if text.trim().is_empty() {
+        eprintln!("Nothing to append");
+        return Err(InkError::Failed);
+    }
+
+    let timestamp = Local::now().format(ENTRY_TIMESTAMP_FORMAT).to_string();
+    let entry = format_entry(&text, &timestamp);
+    let updated = insert_entry(&get_markdown_str(&path)?, &entry, heading);
+    fs::write(&path, updated).map_err(InkError::io(&path))?;
+    println!("{}", path.display());
+    reindex(&path);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTRY: &str = "- 2026-10-18 09:30 Call the plumber\n";
+
+    #[test]
+    fn test_format_entry() {
+        assert_eq!(
+            format_entry("Call the plumber\n", "2026-10-18 09:30"),
+            ENTRY
+        );
+        assert_eq!(
+            format_entry("First line\nsecond line\n\nthird", "2026-10-18 09:30"),
+            "- 2026-10-18 09:30 First line\n  second line\n\n  third\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_entry_at_end() {
+        assert_eq!(
+            insert_entry("---\ntitle: Inbox\n---\nSome text\n", ENTRY, None),
+            format!("---\ntitle: Inbox\n---\nSome text\n\n{ENTRY}")
+        );
+        assert_eq!(
+            insert_entry("- 2026-10-17 18:00 Earlier\n\n", ENTRY, None),
+            format!("- 2026-10-17 18:00 Earlier\n{ENTRY}")
+        );
+        assert_eq!(insert_entry("", ENTRY, None), ENTRY);
+    }
+
+    #[test]
+    fn test_insert_entry_under_heading() {
+        let note = "# Journal\n\n## Log\n\n- 2026-10-17 18:00 Earlier\n\n### Detail\n\nText\n\n## Ideas\n\nMore\n";
+        assert_eq!(
+            insert_entry(note, ENTRY, Some("log")),
+            format!("# Journal\n\n## Log\n\n- 2026-10-17 18:00 Earlier\n\n### Detail\n\nText\n\n{ENTRY}\n## Ideas\n\nMore\n")
+        );
+        assert_eq!(
+            insert_entry("## Empty\n## Next\n", ENTRY, Some("Empty")),
+            format!("## Empty\n\n{ENTRY}\n## Next\n")
+        );
+    }
+
+    #[test]
+    fn test_insert_entry_adds_missing_heading() {
+        assert_eq!(
+            insert_entry("# Journal\n\n```\n## Log\n```\n", ENTRY, Some("Log")),
+            format!("# Journal\n\n```\n## Log\n```\n\n## Log\n\n{ENTRY}")
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use crate::error::{succeeded, InkError};
+use crate::links::{scan_vault, LinkKind};
+use crate::settings::SETTINGS;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+#[derive(Serialize)]
+struct BrokenLink {
+    path: PathBuf,
+    line: usize,
+    target: String,
+    kind: LinkKind,
+}
+
+#[derive(Serialize)]
+struct LinkReport {
+    broken_links: Vec<BrokenLink>,
+    orphans: Vec<PathBuf>,
+}
+
+fn build_link_report() -> Result<LinkReport, InkError> {
+    let (linked_notes, resolver) = scan_vault(&SETTINGS.get_notes_path())?;
+
+    let mut broken_links = Vec::new();
+    let mut linked: HashSet<PathBuf> = HashSet::new();
+    for linked_note in &linked_notes {
+        for link in &linked_note.links {
+            match resolver.resolve(&linked_note.path, link) {
+                Some(target) if target == linked_note.path => {}
+                Some(target) => {
+                    linked.insert(target);
+                    linked.insert(linked_note.path.clone());
+                }
+                None => broken_links.push(BrokenLink {
+                    path: linked_note.path.clone(),
+                    line: link.line,
+                    target: link.target.clone(),
+                    kind: link.kind,
+                }),
+            }
+        }
+    }
+
+    let mut orphans: Vec<PathBuf> = linked_notes
+        .iter()
+        .filter(|linked_note| !linked_note.note.is_hidden())
+        .filter(|linked_note| !linked.contains(&linked_note.path))
+        .map(|linked_note| linked_note.path.clone())
+        .collect();
+    orphans.sort();
+    broken_links.sort_by(|a, b| a.path.cmp(&b.path).then(a.line.cmp(&b.line)));
+
+    Ok(LinkReport {
+        broken_links,
+        orphans,
+    })
+}
+
+/// Report unresolved internal links and orphaned notes.
+///
+/// Fails when any broken links were found.
+pub fn check_links(is_json: bool) -> Result<(), InkError> {
+    let report = build_link_report()?;
+    let is_clean = report.broken_links.is_empty();
+
+    if is_json {
+        println!("{}", serde_json::to_string(&report).unwrap());
+        return succeeded(is_clean);
+    }
+
+    for broken_link in &report.broken_links {
+        println!(
+            "{}:{}\t{}",
+            broken_link.path.display(),
+            broken_link.line,
+            broken_link.target
+        );
+    }
+    for orphan in &report.orphans {
+        println!("orphan\t{}", orphan.display());
+    }
+    eprintln!(
+        "{} broken links, {} orphaned notes",
+        report.broken_links.len(),
+        report.orphans.len()
+    );
+    succeeded(is_clean)
+}
@@ -1,14 +1,38 @@
+use crate::archive::archive;
+use crate::bookmark_check::{check_bookmarks, CheckOptions};
+use crate::bookmark_dedupe::dedupe;
+use crate::bookmark_export::export_bookmarks;
+use crate::bookmark_import::import_bookmarks;
+use crate::bookmark_queue::{queue, random, read};
 use crate::bookmarks::{create_bookmark, mark};
+use crate::capture::{append, capture};
+use crate::check::check_links;
+use crate::doctor::doctor;
+use crate::editor::{edit, parse_line_argument};
+use crate::error::InkError;
+use crate::graph::graph;
+use crate::http::set_offline;
 use crate::list::list;
+use crate::resolve::print_resolved;
 use crate::search::{create_index_and_add_documents, search_index};
 use crate::settings::SETTINGS;
+use crate::show::show;
+use crate::site::export_site;
+use crate::tags::{add_tags, remove_tags, rename_tag};
+use crate::tasks::{complete_task, list_tasks, TaskFilter};
 use crate::write::{prompt as process_prompt, prompt_and_edit};
+use chrono::NaiveDate;
 use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Do not fetch anything from the network
+    #[arg(long, global = true)]
+    offline: bool,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -21,6 +45,40 @@ pub enum SortChoice {
     Created,
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    Graphml,
+    Json,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum BookmarkFormat {
+    Netscape,
+    Jsonfeed,
+    Opml,
+    Csv,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ImportSource {
+    /// A browser's bookmarks.html
+    Netscape,
+    /// Pinboard's JSON export
+    Pinboard,
+    /// Pocket's CSV export
+    Pocket,
+    /// Raindrop.io's CSV export
+    Raindrop,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// View all notes
@@ -42,6 +100,32 @@ enum Commands {
     },
     /// Create + Immediatley edit a new note
     Create { query: String },
+    /// Create a note from stdin, eg `cmd | ink capture "Build log #ci"`
+    Capture {
+        /// Title of the new note, with optional #tags and url
+        query: String,
+    },
+    /// Add a timestamped entry to an existing note
+    Append {
+        /// Path, id or title of the note
+        note: String,
+        /// Text of the entry, read from stdin when omitted
+        text: Option<String>,
+        /// Add the entry under this heading, which is created if missing
+        #[arg(long = "under", short = 'u')]
+        heading: Option<String>,
+    },
+    /// Open an existing note in $VISUAL or $EDITOR
+    Edit {
+        /// Path, id or title of the note, or else a search query
+        query: String,
+        /// Line to open the note at, as `+12` or `12`
+        #[arg(value_parser = parse_line_argument)]
+        position: Option<usize>,
+        /// Open the note at the first line containing this text
+        #[arg(long, short = 's', conflicts_with = "position")]
+        search_term: Option<String>,
+    },
     /// Create a new note, but do not open an edit session
     Prompt {
         query: String,
@@ -66,6 +150,138 @@ enum Commands {
         #[arg(long, short = 'i')]
         include_ignored: bool,
     },
+    /// Add, remove or rename tags in note frontmatter
+    Tag {
+        #[command(subcommand)]
+        action: TagCommands,
+    },
+    /// Export the graph of links and shared tags between notes
+    Graph {
+        /// Output format
+        #[arg(long, short, value_enum, default_value = "dot")]
+        format: GraphFormat,
+        /// Limit the graph to notes matching a search query
+        query: Option<String>,
+        /// Include notes from ignored directories
+        #[arg(long, short = 'i')]
+        include_ignored: bool,
+        /// Do not connect notes that share a tag. Every pair of notes with a
+        /// tag in common gets an edge, so a tag on many notes adds many edges.
+        #[arg(long)]
+        no_tag_edges: bool,
+    },
+    /// Check the notes folder for problems
+    Check {
+        #[command(subcommand)]
+        action: CheckCommands,
+    },
+    /// Report notes with malformed frontmatter, null bytes or duplicate ids
+    Doctor {
+        /// Fix what can be fixed safely, eg scalar tags, null bytes and empty titles
+        #[arg(long)]
+        fix: bool,
+        // Return output as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Render a note to the terminal, or as HTML
+    Show {
+        /// Path, id or title of the note
+        note: String,
+        /// Print a standalone HTML page instead
+        #[arg(long)]
+        html: bool,
+        /// When to style terminal output
+        #[arg(long, value_enum, default_value = "auto")]
+        color: ColorChoice,
+    },
+    /// Print the path of the note matching a path, id or title
+    Resolve {
+        /// Path, id, title, or part of a title
+        query: String,
+    },
+    /// Export notes to other formats
+    Export {
+        #[command(subcommand)]
+        action: ExportCommands,
+    },
+    /// List the `- [ ]` tasks found in notes
+    #[command(args_conflicts_with_subcommands = true)]
+    Tasks {
+        #[command(subcommand)]
+        action: Option<TaskCommands>,
+        /// Only list tasks that are not done
+        #[arg(long, conflicts_with = "done")]
+        open: bool,
+        /// Only list tasks that are done
+        #[arg(long)]
+        done: bool,
+        /// Only list tasks due before this date (YYYY-MM-DD)
+        #[arg(long)]
+        due_before: Option<NaiveDate>,
+        /// #tags to limit results by
+        tags: Vec<String>,
+        /// Include tasks from ignored directories
+        #[arg(long, short = 'i')]
+        include_ignored: bool,
+        // Return output as json
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportCommands {
+    /// Render every visible note to a static HTML site
+    Site {
+        /// Folder to write the site to
+        outdir: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum TaskCommands {
+    /// Tick the checkbox of a task in its note
+    Done {
+        /// Task id, as printed by `ink tasks`
+        id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagCommands {
+    /// Add tags to a note
+    Add {
+        /// Path, id or title of the note, or a search query with --query
+        note: String,
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Treat the note argument as a search query and tag every match
+        #[arg(long, short)]
+        query: bool,
+    },
+    /// Remove tags from a note
+    Rm {
+        /// Path, id or title of the note, or a search query with --query
+        note: String,
+        #[arg(required = true)]
+        tags: Vec<String>,
+        /// Treat the note argument as a search query and untag every match
+        #[arg(long, short)]
+        query: bool,
+    },
+    /// Rename a tag across every note
+    Rename { old: String, new: String },
+}
+
+#[derive(Subcommand)]
+enum CheckCommands {
+    /// Report broken internal links and orphaned notes
+    Links {
+        // Return output as json
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -80,11 +296,131 @@ enum BookmarkCommands {
     Create {
         url: String,
         description: Option<String>,
+        /// Save the text of the page in the note, for search and offline reading
+        #[arg(long)]
+        archive: bool,
+        /// Add the bookmark to the read-later queue
+        #[arg(long)]
+        later: bool,
+    },
+    /// List unread bookmarks in the read-later queue, oldest first
+    Queue {
+        // Return output as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Mark a bookmark as read
+    Read {
+        /// Path, id or title of the bookmark
+        note: String,
+    },
+    /// Pick an unread bookmark from the read-later queue at random
+    Random {
+        // Return output as json
+        #[arg(long)]
+        json: bool,
+    },
+    /// Save the text of a bookmarked page in its note
+    Archive {
+        /// Path, id or title of the bookmark
+        note: String,
+    },
+    /// Import bookmarks from a browser or bookmark service export
+    Import {
+        /// The exported file
+        file: PathBuf,
+        /// Where the export comes from
+        #[arg(long, value_enum, default_value = "netscape")]
+        from: ImportSource,
+        /// Keep the titles from a browser export instead of fetching each page
+        #[arg(long)]
+        no_fetch: bool,
+    },
+    /// Export bookmarks for browsers, feed readers and other tools
+    Export {
+        /// Output format
+        #[arg(long, short, value_enum, default_value = "netscape")]
+        format: BookmarkFormat,
+        /// Tag to limit bookmarks by
+        #[arg(long, short, value_delimiter = ',', action = ArgAction::Append)]
+        tags: Vec<String>,
+        /// Limit the export to bookmarks matching a search query
+        query: Option<String>,
+    },
+    /// Merge bookmarks that point to the same page
+    Dedupe {
+        /// Only list the bookmarks that would be merged
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check every bookmark for dead links and permanent redirects
+    Check {
+        /// Seconds to wait for each response, instead of the `[http]` timeout
+        #[arg(long)]
+        timeout: Option<u64>,
+        /// How many urls to check at once
+        #[arg(long, short, default_value = "8")]
+        concurrency: usize,
+        /// Tag dead bookmarks with `dead-link`
+        #[arg(long)]
+        tag_dead: bool,
+        /// Replace permanently redirected urls with where they now point
+        #[arg(long)]
+        fix_redirects: bool,
+        // Return output as json
+        #[arg(long)]
+        json: bool,
     },
 }
 
-pub fn run_cli() {
+fn run_mark(action: &BookmarkCommands) -> Result<(), InkError> {
+    match action {
+        BookmarkCommands::List { json } => mark(*json),
+        BookmarkCommands::Create {
+            url,
+            description,
+            archive,
+            later,
+        } => create_bookmark(url, description.clone(), *archive, *later),
+        BookmarkCommands::Queue { json } => queue(*json),
+        BookmarkCommands::Read { note } => read(note),
+        BookmarkCommands::Random { json } => random(*json),
+        BookmarkCommands::Archive { note } => archive(note),
+        BookmarkCommands::Import {
+            file,
+            from,
+            no_fetch,
+        } => import_bookmarks(file, *from, !no_fetch),
+        BookmarkCommands::Export {
+            format,
+            tags,
+            query,
+        } => export_bookmarks(*format, tags, query.as_deref()),
+        BookmarkCommands::Dedupe { dry_run } => dedupe(*dry_run),
+        BookmarkCommands::Check {
+            timeout,
+            concurrency,
+            tag_dead,
+            fix_redirects,
+            json,
+        } => {
+            let options = CheckOptions {
+                timeout: timeout.map(Duration::from_secs),
+                concurrency: *concurrency,
+                tag_dead: *tag_dead,
+                fix_redirects: *fix_redirects,
+            };
+            check_bookmarks(&options, *json)
+        }
+    }
+}
+
+/// Run the command given on the command line. Its error decides the exit code.
+pub fn run_cli() -> Result<(), InkError> {
     let cli = Cli::parse();
+    if cli.offline {
+        set_offline();
+    }
 
     match &cli.command {
         Commands::List {
@@ -93,40 +429,77 @@ pub fn run_cli() {
             include_ignored,
         } => {
             let final_recurse = recurse.unwrap_or(SETTINGS.recurse);
-            list(final_recurse, tags, *include_ignored);
-        }
-        Commands::Mark { action } => match action {
-            BookmarkCommands::List { json } => {
-                mark(*json);
-            }
-            BookmarkCommands::Create { url, description } => {
-                create_bookmark(url, description.clone());
-            }
-        },
-        Commands::Create { query } => {
-            prompt_and_edit(query);
+            list(final_recurse, tags, *include_ignored)
         }
+        Commands::Mark { action } => run_mark(action),
+        Commands::Create { query } => prompt_and_edit(query),
+        Commands::Capture { query } => capture(query),
+        Commands::Append {
+            note,
+            text,
+            heading,
+        } => append(note, text.as_deref(), heading.as_deref()),
+        Commands::Edit {
+            query,
+            position,
+            search_term,
+        } => edit(query, *position, search_term.as_deref()),
         Commands::Prompt { path_only, query } => {
-            let note = process_prompt(query);
+            let note = process_prompt(query)?;
             if *path_only {
-                println!("{}", note.get_file_path().to_str().unwrap());
+                println!("{}", note.get_file_path().display());
             } else {
-                println!("Created {} with id {}", note.title, note.id,);
+                println!("Created {} with id {}", note.title, note.id);
             }
+            Ok(())
         }
-        Commands::Index {} => match create_index_and_add_documents() {
-            Ok(()) => (),
-            Err(_) => println!("An error occured indexing"),
-        },
+        Commands::Index {} => create_index_and_add_documents(),
         Commands::Search {
             json,
             query,
             sort,
             limit,
             include_ignored,
-        } => match search_index(query, *json, *sort, *limit, *include_ignored) {
-            Ok(()) => (),
-            Err(e) => println!("Could not complete a search {e}"),
+        } => Ok(search_index(query, *json, *sort, *limit, *include_ignored)?),
+        Commands::Tag { action } => match action {
+            TagCommands::Add { note, tags, query } => add_tags(note, tags, *query),
+            TagCommands::Rm { note, tags, query } => remove_tags(note, tags, *query),
+            TagCommands::Rename { old, new } => rename_tag(old, new),
+        },
+        Commands::Graph {
+            format,
+            query,
+            include_ignored,
+            no_tag_edges,
+        } => graph(*format, query.as_deref(), *include_ignored, !*no_tag_edges),
+        Commands::Check { action } => match action {
+            CheckCommands::Links { json } => check_links(*json),
         },
+        Commands::Export { action } => match action {
+            ExportCommands::Site { outdir } => {
+                let count = export_site(outdir)?;
+                println!("Exported {count} notes to {}", outdir.display());
+                Ok(())
+            }
+        },
+        Commands::Doctor { fix, json } => doctor(*fix, *json),
+        Commands::Resolve { query } => print_resolved(query),
+        Commands::Show { note, html, color } => show(note, *html, *color),
+        Commands::Tasks {
+            action: Some(TaskCommands::Done { id }),
+            ..
+        } => complete_task(id),
+        Commands::Tasks {
+            action: None,
+            open,
+            done,
+            due_before,
+            tags,
+            include_ignored,
+            json,
+        } => {
+            let filter = TaskFilter::from_flags(*open, *done, *due_before, tags, *include_ignored);
+            list_tasks(&filter, *json)
+        }
     }
 }
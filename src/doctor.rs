@@ -0,0 +1,414 @@
+use crate::error::{succeeded, InkError};
+use crate::frontmatter::{detect_format, read_tags, FrontMatterEditor, FrontMatterFormat};
+use crate::markdown::get_markdown_str;
+use crate::page::PageMetadata;
+use crate::search::reindex_files;
+use crate::settings::SETTINGS;
+use crate::walk::{has_extension, walk_files};
+use gray_matter::engine::{Engine, TOML, YAML};
+use gray_matter::Matter;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum ProblemKind {
+    /// The file could not be read, or is not UTF-8
+    Unreadable,
+    /// The frontmatter is not valid YAML, TOML or JSON
+    InvalidFrontmatter,
+    /// A key ink reads has a value of the wrong type, so the whole frontmatter is ignored
+    WrongType,
+    NullByte,
+    EmptyTitle,
+    /// Several notes have the same file name, so only one can be found by id
+    DuplicateId,
+}
+
+impl ProblemKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            ProblemKind::Unreadable => "unreadable",
+            ProblemKind::InvalidFrontmatter => "invalid-frontmatter",
+            ProblemKind::WrongType => "wrong-type",
+            ProblemKind::NullByte => "null-byte",
+            ProblemKind::EmptyTitle => "empty-title",
+            ProblemKind::DuplicateId => "duplicate-id",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Problem {
+    path: PathBuf,
+    kind: ProblemKind,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct DoctorReport {
+    problems: Vec<Problem>,
+    fixed: Vec<PathBuf>,
+}
+
+/// The type a frontmatter key must have for ink to use it.
+#[derive(Clone, Copy)]
+enum Expected {
+    Text,
+    List,
+    Bool,
+}
+
+impl Expected {
+    fn for_key(key: &str) -> Option<Self> {
+        match key {
+            "tags" => Some(Expected::List),
+            "read" => Some(Expected::Bool),
+            "title" | "url" | "created" => Some(Expected::Text),
+            _ if PageMetadata::default()
+                .fields()
+                .iter()
+                .any(|(name, _)| *name == key) =>
+            {
+                Some(Expected::Text)
+            }
+            _ => None,
+        }
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match (self, value) {
+            (_, Value::Null)
+            | (Expected::Text, Value::String(_))
+            | (Expected::Bool, Value::Bool(_)) => true,
+            (Expected::List, Value::Array(items)) => items.iter().all(Value::is_string),
+            _ => false,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Expected::Text => "a string",
+            Expected::List => "a list of strings",
+            Expected::Bool => "true or false",
+        }
+    }
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "nothing",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(items) if items.iter().all(Value::is_string) => "a list of strings",
+        Value::Array(_) => "a list with values that are not strings",
+        Value::Object(_) => "a map",
+    }
+}
+
+fn contains_null(value: &Value) -> bool {
+    match value {
+        Value::String(text) => text.contains('\0'),
+        Value::Array(items) => items.iter().any(contains_null),
+        Value::Object(values) => values.values().any(contains_null),
+        _ => false,
+    }
+}
+
+fn parse_delimited<T: Engine>(raw: &str, delimiter: &str) -> Result<Option<Value>, String> {
+    let mut matter = Matter::<T>::new();
+    matter.delimiter = delimiter.to_string();
+    matter
+        .parse::<Value>(raw)
+        .map(|entity| entity.data)
+        .map_err(|e| e.to_string())
+}
+
+/// The frontmatter keys of a note as untyped values, or why they could not be parsed.
+///
+/// `markdown::frontmatter` quietly drops frontmatter it cannot read, which is
+/// exactly what needs reporting here.
+fn frontmatter_values(raw: &str) -> Result<Option<Map<String, Value>>, String> {
+    let raw = raw.strip_prefix('\u{feff}').unwrap_or(raw);
+    let data = match detect_format(raw) {
+        None => return Ok(None),
+        Some(FrontMatterFormat::Yaml) => parse_delimited::<YAML>(raw, "---")?,
+        Some(FrontMatterFormat::Toml) => parse_delimited::<TOML>(raw, "+++")?,
+        Some(FrontMatterFormat::Json) => serde_json::Deserializer::from_str(raw)
+            .into_iter()
+            .next()
+            .and_then(Result::ok),
+    };
+    match data {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Object(values)) => Ok(Some(values)),
+        Some(other) => Err(format!(
+            "the frontmatter is {} instead of keys and values",
+            describe(&other)
+        )),
+    }
+}
+
+fn has_empty_title(values: &Map<String, Value>) -> bool {
+    match values.get("title") {
+        Some(Value::Null) => true,
+        Some(Value::String(title)) => title.replace('\0', "").trim().is_empty(),
+        _ => false,
+    }
+}
+
+/// The problems with a single note.
+fn diagnose(raw: &str) -> Vec<(ProblemKind, String)> {
+    let mut problems = Vec::new();
+    if FrontMatterEditor::new(raw).body().contains('\0') {
+        problems.push((
+            ProblemKind::NullByte,
+            "the body contains null bytes".to_string(),
+        ));
+    }
+    let values = match frontmatter_values(raw) {
+        Ok(Some(values)) => values,
+        Ok(None) => return problems,
+        Err(message) => {
+            problems.push((ProblemKind::InvalidFrontmatter, message));
+            return problems;
+        }
+    };
+    for (key, value) in &values {
+        if let Some(expected) = Expected::for_key(key).filter(|expected| !expected.matches(value)) {
+            problems.push((
+                ProblemKind::WrongType,
+                format!(
+                    "{key} should be {}, found {}",
+                    expected.describe(),
+                    describe(value)
+                ),
+            ));
+        }
+        if contains_null(value) {
+            problems.push((ProblemKind::NullByte, format!("{key} contains a null byte")));
+        }
+    }
+    if has_empty_title(&values) {
+        problems.push((ProblemKind::EmptyTitle, "title is empty".to_string()));
+    }
+    problems
+}
+
+/// Repair what can be repaired without guessing: null bytes are dropped, scalar
+/// tags become a list, numbers and booleans where text belongs are quoted, and
+/// an empty title is removed so the heading or file name is used instead.
+fn fix_markdown(raw: &str) -> String {
+    let raw = raw.replace('\0', "");
+    let Ok(Some(values)) = frontmatter_values(&raw) else {
+        return raw;
+    };
+    let mut editor = FrontMatterEditor::new(&raw);
+    for (key, value) in &values {
+        match (Expected::for_key(key), value) {
+            (Some(Expected::List), Value::Array(items))
+                if contains_null(value) && Expected::List.matches(value) =>
+            {
+                let tags: Vec<String> = items
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|tag| tag.replace('\0', ""))
+                    .filter(|tag| !tag.trim().is_empty())
+                    .collect();
+                editor.set(key, tags);
+            }
+            // Read the items back from the raw text, the way `ink tag` does
+            (Some(Expected::List), Value::String(_) | Value::Number(_) | Value::Array(_))
+                if !Expected::List.matches(value) =>
+            {
+                editor.set(key, read_tags(&raw));
+            }
+            (Some(Expected::Text), Value::String(text)) if text.contains('\0') => {
+                editor.set(key, text.replace('\0', ""));
+            }
+            (Some(Expected::Text), Value::Number(_) | Value::Bool(_)) => {
+                if let Some(text) = editor.get_str(key) {
+                    editor.set(key, text);
+                }
+            }
+            _ => {}
+        }
+    }
+    if has_empty_title(&values) {
+        editor.remove("title");
+    }
+    editor.into_string()
+}
+
+/// The message of an error about one file, without the path it is reported under.
+fn file_error_message(error: InkError) -> String {
+    match error {
+        InkError::Io { source, .. } => source.to_string(),
+        InkError::Parse { message, .. } => message,
+        other => other.to_string(),
+    }
+}
+
+fn scan(fix: bool) -> Result<DoctorReport, InkError> {
+    let mut problems = Vec::new();
+    let mut fixed = Vec::new();
+    let mut ids: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    let mut add = |path: &Path, kind: ProblemKind, message: String| {
+        problems.push(Problem {
+            path: path.to_path_buf(),
+            kind,
+            message,
+        });
+    };
+    walk_files(&SETTINGS.get_notes_path(), true, has_extension, |path| {
+        if let Some(stem) = path.file_stem() {
+            ids.entry(stem.to_string_lossy().into_owned())
+                .or_default()
+                .push(path.to_path_buf());
+        }
+        let raw = match get_markdown_str(path) {
+            Ok(raw) => raw,
+            Err(e) if e.is_file_error() => {
+                add(path, ProblemKind::Unreadable, file_error_message(e));
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        let mut found = diagnose(&raw);
+        if fix && !found.is_empty() {
+            let fixed_markdown = fix_markdown(&raw);
+            if fixed_markdown != raw {
+                fs::write(path, &fixed_markdown).map_err(InkError::io(path))?;
+                fixed.push(path.to_path_buf());
+                found = diagnose(&fixed_markdown);
+            }
+        }
+        for (kind, message) in found {
+            add(path, kind, message);
+        }
+        Ok(())
+    })?;
+
+    for (id, paths) in &ids {
+        for path in paths.iter().filter(|_| paths.len() > 1) {
+            let others: Vec<String> = paths
+                .iter()
+                .filter(|other| *other != path)
+                .map(|other| other.display().to_string())
+                .collect();
+            add(
+                path,
+                ProblemKind::DuplicateId,
+                format!("the id {id} is also used by {}", others.join(", ")),
+            );
+        }
+    }
+
+    problems.sort_by(|a, b| a.path.cmp(&b.path));
+    fixed.sort();
+    Ok(DoctorReport { problems, fixed })
+}
+
+/// Report notes with frontmatter or data that ink cannot read, fixing what is
+/// safe to fix when `fix` is set.
+///
+/// Fails when any problems are left.
+pub fn doctor(fix: bool, is_json: bool) -> Result<(), InkError> {
+    let report = scan(fix)?;
+    if !report.fixed.is_empty() {
+        if let Err(e) = reindex_files(&report.fixed) {
+            eprintln!("Could not update the index {e}");
+        }
+    }
+    let is_clean = report.problems.is_empty();
+
+    if is_json {
+        println!("{}", serde_json::to_string(&report).unwrap());
+        return succeeded(is_clean);
+    }
+
+    for path in &report.fixed {
+        println!("Fixed {}", path.display());
+    }
+    for problem in &report.problems {
+        println!(
+            "{}\t{}\t{}",
+            problem.path.display(),
+            problem.kind.as_str(),
+            problem.message
+        );
+    }
+    eprintln!(
+        "{} problems, {} notes fixed",
+        report.problems.len(),
+        report.fixed.len()
+    );
+    succeeded(is_clean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(raw: &str) -> Vec<ProblemKind> {
+        diagnose(raw).into_iter().map(|(kind, _)| kind).collect()
+    }
+
+    #[test]
+    fn test_diagnose() {
+        assert!(kinds("---\ntitle: Fine\ntags: [a, b]\nread: false\n---\nBody\n").is_empty());
+        assert!(kinds("No frontmatter\n").is_empty());
+        assert_eq!(
+            kinds("---\ntitle: [unclosed\n---\n"),
+            [ProblemKind::InvalidFrontmatter]
+        );
+        assert_eq!(
+            diagnose("---\ntitle: Post\ntags: foo\n---\n"),
+            [(
+                ProblemKind::WrongType,
+                "tags should be a list of strings, found a string".to_string()
+            )]
+        );
+        assert_eq!(
+            kinds("+++\ntitle = 2024\nread = \"yes\"\n+++\n"),
+            [ProblemKind::WrongType, ProblemKind::WrongType]
+        );
+        assert_eq!(
+            kinds("---\ntitle: \"\"\ntags: [\"tag\\0prompt\"]\n---\nBody\0\n"),
+            [
+                ProblemKind::NullByte,
+                ProblemKind::NullByte,
+                ProblemKind::EmptyTitle
+            ]
+        );
+        assert_eq!(
+            kinds("{\"title\": \"Json\", \"tags\": \"one\"}\n"),
+            [ProblemKind::WrongType]
+        );
+    }
+
+    #[test]
+    fn test_fix_markdown() {
+        assert_eq!(
+            fix_markdown("---\ntitle: 2024\ntags: foo, bar\nauthor: Ann\n---\nBody\n"),
+            "---\ntitle: \"2024\"\ntags: [foo, bar]\nauthor: Ann\n---\nBody\n"
+        );
+        assert_eq!(
+            fix_markdown("---\ntitle: \"\"\ntags: [\"tag\\0prompt\", ok]\n---\nBody\0\n"),
+            "---\ntags: [tagprompt, ok]\n---\nBody\n"
+        );
+        let invalid = "---\ntitle: [unclosed\n---\n";
+        assert_eq!(fix_markdown(invalid), invalid);
+        for raw in [
+            "---\ntitle: 2024\ntags: foo\n---\n",
+            "+++\ntitle = 7\ntags = \"a b\"\n+++\n",
+            "{\n  \"title\": 7,\n  \"tags\": \"one\"\n}\n",
+        ] {
+            assert!(diagnose(&fix_markdown(raw)).is_empty(), "{raw}");
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::error::InkError;
+use crate::markdown::get_markdown_str_read_only;
+use crate::resolve::{resolve_note, ResolveError};
+use crate::search::find_notes;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How an editor is told which line to open a file at.
+///
+/// `{file}` and `{line}` in each argument are replaced before launching.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditorProfile {
+    /// `vim +12 note.md`, also used by nvim, vi, nano, kakoune and emacs
+    PlusLine,
+    /// `hx note.md:12`
+    FileColonLine,
+    /// `code --goto note.md:12`
+    Goto,
+    /// Editors we do not know how to position
+    FileOnly,
+}
+
+impl EditorProfile {
+    fn for_program(program: &str) -> Self {
+        let name = Path::new(program)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(program);
+        match name {
+            "vim" | "nvim" | "vi" | "gvim" | "mvim" | "nano" | "kak" | "emacs" | "emacsclient" => {
+                EditorProfile::PlusLine
+            }
+            "hx" | "helix" => EditorProfile::FileColonLine,
+            "code" | "code-insiders" | "codium" | "cursor" => EditorProfile::Goto,
+            _ => EditorProfile::FileOnly,
+        }
+    }
+
+    fn template(self) -> &'static [&'static str] {
+        match self {
+            EditorProfile::PlusLine => &["+{line}", "{file}"],
+            EditorProfile::FileColonLine => &["{file}:{line}"],
+            EditorProfile::Goto => &["--goto", "{file}:{line}"],
+            EditorProfile::FileOnly => &["{file}"],
+        }
+    }
+}
+
+/// The editor command, from `$VISUAL` then `$EDITOR`, falling back to `vim`.
+fn get_editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| String::from("vim"))
+}
+
+/// Split an editor command such as `code --wait` into words, honouring quotes and backslashes.
+fn split_command(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(escaped) = chars.next() {
+                    word.push(escaped);
+                }
+                in_word = true;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// The full argument list to launch `editor` on `path`, optionally at `line`.
+fn editor_args(editor: &str, path: &Path, line: Option<usize>) -> Vec<String> {
+    let mut args = split_command(editor);
+    if args.is_empty() {
+        args.push(String::from("vim"));
+    }
+    let file = path.to_string_lossy();
+    match line {
+        Some(line) => {
+            let line = line.to_string();
+            let template = EditorProfile::for_program(&args[0]).template();
+            args.extend(
+                template
+                    .iter()
+                    .map(|arg| arg.replace("{file}", &file).replace("{line}", &line)),
+            );
+        }
+        None => args.push(file.into_owned()),
+    }
+    args
+}
+
+/// Open a file in the user's editor and wait for it to exit.
+pub fn open_in_editor(path: &Path, line: Option<usize>) -> Result<(), InkError> {
+    let args = editor_args(&get_editor(), path, line);
+    let status = Command::new(&args[0])
+        .args(&args[1..])
+        .status()
+        .map_err(InkError::io(Path::new(&args[0])))?;
+    if !status.success() {
+        eprintln!("{} exited with {status}", args[0]);
+        return Err(InkError::Failed);
+    }
+    Ok(())
+}
+
+/// The 1-based number of the first line containing `term`, ignoring case.
+fn find_line(content: &str, term: &str) -> Option<usize> {
+    let term = term.to_lowercase();
+    content
+        .lines()
+        .position(|line| line.to_lowercase().contains(&term))
+        .map(|index| index + 1)
+}
+
+/// A note argument, or the best search hit when nothing else matches.
+fn find_note_to_edit(query: &str) -> Result<PathBuf, InkError> {
+    match resolve_note(query) {
+        Err(e @ InkError::Resolve(ResolveError::NotFound(_))) => {
+            match find_notes(query, None, 1, false)?.first() {
+                Some(note) => Ok(note.get_file_path()),
+                None => Err(e),
+            }
+        }
+        result => result,
+    }
+}
+
+/// Open an existing note, at `line` or at the first line containing `search_term`.
+pub fn edit(query: &str, line: Option<usize>, search_term: Option<&str>) -> Result<(), InkError> {
+    let path = find_note_to_edit(query)?;
+    let line = match (line, search_term) {
+        (Some(line), _) => Some(line),
+        (None, Some(term)) => {
+            let found = find_line(&get_markdown_str_read_only(&path)?, term);
+            if found.is_none() {
+                eprintln!("{term} not found in {}", path.display());
+            }
+            found
+        }
+        (None, None) => None,
+    };
+    open_in_editor(&path, line)
+}
+
+/// Parse a vim style `+12` line argument.
+pub fn parse_line_argument(value: &str) -> Result<usize, String> {
+    value
+        .strip_prefix('+')
+        .unwrap_or(value)
+        .parse::<usize>()
+        .map_err(|_| format!("expected a line number like +12, got {value}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(split_command("vim"), vec!["vim"]);
+        assert_eq!(split_command("  code --wait "), vec!["code", "--wait"]);
+        assert_eq!(
+            split_command(r#"'/Applications/My Editor' -a "two words" with\ space ''"#),
+            vec![
+                "/Applications/My Editor",
+                "-a",
+                "two words",
+                "with space",
+                ""
+            ]
+        );
+    }
+
+    #[test]
+    fn test_editor_args() {
+        let path = Path::new("/notes/todo.md");
+        assert_eq!(
+            editor_args("vim", path, None),
+            vec!["vim", "/notes/todo.md"]
+        );
+        assert_eq!(
+            editor_args("/usr/bin/nvim", path, Some(3)),
+            vec!["/usr/bin/nvim", "+3", "/notes/todo.md"]
+        );
+        assert_eq!(
+            editor_args("hx", path, Some(3)),
+            vec!["hx", "/notes/todo.md:3"]
+        );
+        assert_eq!(
+            editor_args("code --wait", path, Some(3)),
+            vec!["code", "--wait", "--goto", "/notes/todo.md:3"]
+        );
+        assert_eq!(
+            editor_args("emacsclient -t", path, Some(3)),
+            vec!["emacsclient", "-t", "+3", "/notes/todo.md"]
+        );
+        assert_eq!(
+            editor_args("ed", path, Some(3)),
+            vec!["ed", "/notes/todo.md"]
+        );
+    }
+
+    #[test]
+    fn test_find_line() {
+        let content = "# Title\n\nSome text\nMore TEXT\n";
+        assert_eq!(find_line(content, "text"), Some(3));
+        assert_eq!(find_line(content, "more text"), Some(4));
+        assert_eq!(find_line(content, "missing"), None);
+    }
+
+    #[test]
+    fn test_parse_line_argument() {
+        assert_eq!(parse_line_argument("+12"), Ok(12));
+        assert_eq!(parse_line_argument("7"), Ok(7));
+        assert!(parse_line_argument("+x").is_err());
+    }
+}
@@ -0,0 +1,191 @@
+//! The errors a command can end with, and the exit code each one maps to.
+//!
+//! | code | meaning                                                 |
+//! |------|---------------------------------------------------------|
+//! | 1    | the command ran but reported problems, eg dead links    |
+//! | 2    | invalid arguments, as reported by clap                  |
+//! | 3    | the configuration, `[http]` included, could not be used |
+//! | 4    | a file could not be read or written                     |
+//! | 5    | a note or import file could not be parsed               |
+//! | 6    | the search index could not be opened or updated         |
+//! | 7    | a page could not be fetched                             |
+//! | 8    | no single note matches the note argument                |
+//! | 9    | the note template could not be rendered                 |
+use crate::http::FetchError;
+use crate::resolve::ResolveError;
+use config::ConfigError;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use tantivy::TantivyError;
+
+#[derive(Debug)]
+pub enum InkError {
+    /// Problems were found and already reported
+    Failed,
+    Config(ConfigError),
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        message: String,
+    },
+    Template(minijinja::Error),
+    Index(TantivyError),
+    Network(FetchError),
+    Resolve(ResolveError),
+}
+
+pub type Result<T> = std::result::Result<T, InkError>;
+
+impl InkError {
+    /// Wrap an IO error with the file it happened on, for use with `map_err`.
+    pub fn io(path: &Path) -> impl FnOnce(io::Error) -> InkError + '_ {
+        move |source| InkError::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn parse(path: &Path, message: impl fmt::Display) -> InkError {
+        InkError::Parse {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Whether the error is about a single file, which a walk over the vault can skip.
+    pub fn is_file_error(&self) -> bool {
+        matches!(self, InkError::Io { .. } | InkError::Parse { .. })
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            InkError::Failed => 1,
+            // Invalid `[http]` settings are a configuration problem, not a network one
+            InkError::Config(_) | InkError::Network(FetchError::Config(_)) => 3,
+            InkError::Io { .. } => 4,
+            InkError::Parse { .. } => 5,
+            InkError::Index(_) => 6,
+            InkError::Network(_) => 7,
+            InkError::Resolve(_) => 8,
+            InkError::Template(_) => 9,
+        }
+    }
+
+    /// Report the error, unless it already was, and exit with its code.
+    pub fn exit(&self) -> ! {
+        if !matches!(self, InkError::Failed) {
+            eprintln!("{self}");
+        }
+        std::process::exit(self.exit_code())
+    }
+}
+
+impl fmt::Display for InkError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InkError::Failed => write!(f, "The command failed"),
+            InkError::Config(e) => write!(f, "Could not load the configuration, {e}"),
+            InkError::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            InkError::Parse { path, message } => write!(f, "{}: {message}", path.display()),
+            InkError::Template(e) => write!(f, "Could not render the template, {e}"),
+            InkError::Index(e) => write!(f, "Search index error, {e}"),
+            InkError::Network(e) => write!(f, "Could not fetch the page, {e}"),
+            InkError::Resolve(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for InkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InkError::Config(e) => Some(e),
+            InkError::Io { source, .. } => Some(source),
+            InkError::Template(e) => Some(e),
+            InkError::Index(e) => Some(e),
+            InkError::Network(e) => Some(e),
+            InkError::Failed | InkError::Parse { .. } | InkError::Resolve(_) => None,
+        }
+    }
+}
+
+impl From<ConfigError> for InkError {
+    fn from(e: ConfigError) -> Self {
+        InkError::Config(e)
+    }
+}
+
+impl From<minijinja::Error> for InkError {
+    fn from(e: minijinja::Error) -> Self {
+        InkError::Template(e)
+    }
+}
+
+impl From<TantivyError> for InkError {
+    fn from(e: TantivyError) -> Self {
+        InkError::Index(e)
+    }
+}
+
+impl From<FetchError> for InkError {
+    fn from(e: FetchError) -> Self {
+        InkError::Network(e)
+    }
+}
+
+impl From<ResolveError> for InkError {
+    fn from(e: ResolveError) -> Self {
+        InkError::Resolve(e)
+    }
+}
+
+/// `Failed` when a command that reports its own problems did not succeed.
+pub fn succeeded(ok: bool) -> Result<()> {
+    if ok {
+        Ok(())
+    } else {
+        Err(InkError::Failed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            InkError::Failed,
+            InkError::Config(ConfigError::Message("bad".to_string())),
+            InkError::io(Path::new("note.md"))(io::Error::from(io::ErrorKind::NotFound)),
+            InkError::parse(Path::new("export.csv"), "missing url column"),
+            InkError::Template(minijinja::Error::new(
+                minijinja::ErrorKind::SyntaxError,
+                "unexpected end of input",
+            )),
+            InkError::Index(TantivyError::InvalidArgument("bad".to_string())),
+            InkError::Network(FetchError::Offline),
+            InkError::Resolve(ResolveError::NotFound("missing".to_string())),
+        ];
+        let codes: HashSet<i32> = errors.iter().map(InkError::exit_code).collect();
+        assert!(!codes.contains(&0) && !codes.contains(&2));
+        assert_eq!(codes.len(), errors.len());
+
+        let http_config = InkError::Network(FetchError::Config("bad proxy".to_string()));
+        assert_eq!(http_config.exit_code(), errors[1].exit_code());
+    }
+
+    #[test]
+    fn test_io_error_names_the_file() {
+        let error = InkError::io(Path::new("notes/todo.md"))(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "permission denied",
+        ));
+        assert_eq!(error.to_string(), "notes/todo.md: permission denied");
+        assert_eq!(error.exit_code(), 4);
+    }
+}
@@ -0,0 +1,1114 @@
+//! Round-trip-safe editing of note frontmatter.
+//!
+//! `markdown::frontmatter` parses frontmatter into typed values but cannot write
+//! it back. The editor here works on the raw text instead: it rewrites only the
+//! keys it is asked to change, so key order, comments, quoting, line endings and
+//! the markdown body are preserved byte-for-byte.
+//!
+//! JSON frontmatter has no comments or quoting choices, so a JSON object is
+//! re-serialized with its original key order and indentation instead.
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::fmt::Write;
+
+const BOM: &str = "\u{feff}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FrontMatterFormat {
+    /// `---` delimited YAML
+    #[default]
+    Yaml,
+    /// `+++` delimited TOML
+    Toml,
+    /// A JSON object at the very start of the file, as used by Hugo
+    Json,
+}
+
+impl FrontMatterFormat {
+    /// The delimiter line of YAML and TOML frontmatter. JSON has none and is
+    /// edited as a whole object instead, so the line-based helpers below only
+    /// ever see YAML and TOML.
+    fn delimiter(self) -> &'static str {
+        if self == FrontMatterFormat::Toml {
+            "+++"
+        } else {
+            "---"
+        }
+    }
+
+    fn separator(self) -> char {
+        if self == FrontMatterFormat::Toml {
+            '='
+        } else {
+            ':'
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrontMatterValue {
+    String(String),
+    Bool(bool),
+    Integer(i64),
+    List(Vec<String>),
+}
+
+impl From<&str> for FrontMatterValue {
+    fn from(value: &str) -> Self {
+        FrontMatterValue::String(value.to_string())
+    }
+}
+
+impl From<String> for FrontMatterValue {
+    fn from(value: String) -> Self {
+        FrontMatterValue::String(value)
+    }
+}
+
+impl From<bool> for FrontMatterValue {
+    fn from(value: bool) -> Self {
+        FrontMatterValue::Bool(value)
+    }
+}
+
+impl From<i64> for FrontMatterValue {
+    fn from(value: i64) -> Self {
+        FrontMatterValue::Integer(value)
+    }
+}
+
+impl From<Vec<String>> for FrontMatterValue {
+    fn from(value: Vec<String>) -> Self {
+        FrontMatterValue::List(value)
+    }
+}
+
+impl From<&FrontMatterValue> for Value {
+    fn from(value: &FrontMatterValue) -> Self {
+        match value {
+            FrontMatterValue::String(text) => Value::String(text.clone()),
+            FrontMatterValue::Bool(flag) => Value::Bool(*flag),
+            FrontMatterValue::Integer(number) => Value::from(*number),
+            FrontMatterValue::List(items) => {
+                Value::Array(items.iter().cloned().map(Value::String).collect())
+            }
+        }
+    }
+}
+
+/// The location of a frontmatter block within a raw markdown file.
+struct FrontMatterBlock {
+    format: FrontMatterFormat,
+    /// Byte offset of the first line after the opening delimiter,
+    /// or of the opening brace for JSON
+    start: usize,
+    /// Byte offset of the closing delimiter line,
+    /// or just past the closing brace for JSON
+    end: usize,
+}
+
+/// How a list was written, so it can be written back the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ListStyle {
+    Flow,
+    Block(String),
+}
+
+/// What is known about a key's current value, used to write a new one in the same style.
+#[derive(Debug, Default)]
+struct ExistingValue {
+    /// The text up to and including the separator, e.g. `title:` or `title =`
+    prefix: String,
+    scalar: Option<String>,
+    quote: Option<char>,
+    list: Option<(Vec<String>, ListStyle)>,
+    /// A trailing comment, with its leading whitespace
+    comment: Option<String>,
+}
+
+fn line_ending(raw: &str) -> &'static str {
+    if raw.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+fn find_json_frontmatter(raw: &str, offset: usize) -> Option<FrontMatterBlock> {
+    let mut stream =
+        serde_json::Deserializer::from_str(&raw[offset..]).into_iter::<Map<String, Value>>();
+    match stream.next() {
+        Some(Ok(_)) => Some(FrontMatterBlock {
+            format: FrontMatterFormat::Json,
+            start: offset,
+            end: offset + stream.byte_offset(),
+        }),
+        _ => None,
+    }
+}
+
+fn find_frontmatter(raw: &str) -> Option<FrontMatterBlock> {
+    let offset = if raw.starts_with(BOM) { BOM.len() } else { 0 };
+    if raw[offset..].starts_with('{') {
+        return find_json_frontmatter(raw, offset);
+    }
+    let first_line_end = offset + raw[offset..].find('\n')?;
+    let format = match raw[offset..first_line_end].trim_end() {
+        "---" => FrontMatterFormat::Yaml,
+        "+++" => FrontMatterFormat::Toml,
+        _ => return None,
+    };
+    let start = first_line_end + 1;
+    let mut line_start = start;
+    for line in raw[start..].split_inclusive('\n') {
+        let trimmed = line.trim_end();
+        let is_closing = trimmed == format.delimiter()
+            || (format == FrontMatterFormat::Yaml && trimmed == "...");
+        if is_closing {
+            return Some(FrontMatterBlock {
+                format,
+                start,
+                end: line_start,
+            });
+        }
+        line_start += line.len();
+    }
+    None
+}
+
+fn is_key_line(format: FrontMatterFormat, line: &str, key: &str) -> bool {
+    let Some(rest) = line.strip_prefix(key) else {
+        return false;
+    };
+    if format == FrontMatterFormat::Toml {
+        rest.trim_start().starts_with('=')
+    } else {
+        rest.starts_with(':')
+    }
+}
+
+/// Count unbalanced `[` in a line, ignoring brackets inside quotes and comments.
+fn bracket_depth(line: &str) -> i32 {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('[', None) => depth += 1,
+            (']', None) => depth -= 1,
+            ('#', None) => break,
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// Find the byte range of `key` within the frontmatter, including its continuation lines.
+fn find_key(raw: &str, block: &FrontMatterBlock, key: &str) -> Option<(usize, usize)> {
+    let mut line_start = block.start;
+    let mut key_range: Option<(usize, usize)> = None;
+    let mut depth = 0;
+    for line in raw[block.start..block.end].split_inclusive('\n') {
+        let line_end = line_start + line.len();
+        if let Some((start, end)) = key_range {
+            let is_blank = line.trim().is_empty();
+            let continues = if block.format == FrontMatterFormat::Toml {
+                depth > 0
+            } else {
+                // Blank lines only belong to the key if more of its value follows
+                !is_blank && line.starts_with([' ', '\t', '-'])
+            };
+            if continues {
+                depth += bracket_depth(line);
+                key_range = Some((start, line_end));
+            } else if !is_blank || block.format == FrontMatterFormat::Toml {
+                return Some((start, end));
+            }
+        } else if block.format == FrontMatterFormat::Toml && line.starts_with('[') {
+            // Only top-level TOML keys are editable
+            return None;
+        } else if is_key_line(block.format, line, key) {
+            depth = bracket_depth(line);
+            key_range = Some((line_start, line_end));
+        }
+        line_start = line_end;
+    }
+    key_range
+}
+
+/// Detect the frontmatter format of a raw markdown file from its opening delimiter.
+pub fn detect_format(raw: &str) -> Option<FrontMatterFormat> {
+    find_frontmatter(raw).map(|block| block.format)
+}
+
+/// Where a new key should go: the end of the block, or before the first TOML table.
+fn insertion_point(raw: &str, block: &FrontMatterBlock) -> usize {
+    if block.format == FrontMatterFormat::Toml {
+        let mut line_start = block.start;
+        for line in raw[block.start..block.end].split_inclusive('\n') {
+            if line.starts_with('[') {
+                return line_start;
+            }
+            line_start += line.len();
+        }
+    }
+    block.end
+}
+
+fn unescape_double_quoted(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Split a scalar into its unquoted value, the quote character used and any trailing comment.
+fn split_scalar(text: &str) -> (String, Option<char>, Option<String>) {
+    let trimmed = text.trim();
+    let mut chars = trimmed.char_indices();
+    if let Some((_, quote @ ('"' | '\''))) = chars.next() {
+        let mut escaped = false;
+        for (i, c) in chars {
+            if quote == '"' && c == '\\' && !escaped {
+                escaped = true;
+                continue;
+            }
+            if c == quote && !escaped {
+                let inner = &trimmed[1..i];
+                let value = if quote == '"' {
+                    unescape_double_quoted(inner)
+                } else {
+                    inner.replace("''", "'")
+                };
+                let rest = &trimmed[i + 1..];
+                let comment = rest.trim().starts_with('#').then(|| rest.to_string());
+                return (value, Some(quote), comment);
+            }
+            escaped = false;
+        }
+    }
+
+    let comment_start = trimmed
+        .char_indices()
+        .find(|&(i, c)| c == '#' && (i == 0 || trimmed[..i].ends_with([' ', '\t'])))
+        .map(|(i, _)| i);
+    match comment_start {
+        Some(i) => {
+            let value = trimmed[..i].trim_end();
+            let comment = &trimmed[value.len()..];
+            (value.to_string(), None, Some(comment.to_string()))
+        }
+        None => (trimmed.to_string(), None, None),
+    }
+}
+
+/// Split a flow sequence body (`a, "b, c", d`) on commas outside of quotes.
+fn split_flow_items(items: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    for c in items.chars() {
+        match (c, quote) {
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (c, Some(q)) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (',', None) => result.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    result.push(current);
+    result
+        .iter()
+        .map(|item| split_scalar(item).0)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn parse_flow_list(prefix: String, value_text: &str) -> ExistingValue {
+    let joined: String = value_text
+        .lines()
+        .map(|line| line.split(" #").next().unwrap_or_default().trim())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let open = joined.find('[').map_or(0, |i| i + 1);
+    let close = joined.rfind(']').unwrap_or(joined.len()).max(open);
+    let first_line = value_text.lines().next().unwrap_or_default();
+    // Only a single-line list can carry a comment we know how to put back
+    let comment = first_line
+        .rfind(']')
+        .map(|i| &first_line[i + 1..])
+        .filter(|rest| rest.trim().starts_with('#'))
+        .map(str::to_string);
+    ExistingValue {
+        prefix,
+        list: Some((split_flow_items(&joined[open..close]), ListStyle::Flow)),
+        comment,
+        ..ExistingValue::default()
+    }
+}
+
+fn parse_existing(format: FrontMatterFormat, key_text: &str) -> ExistingValue {
+    let Some(separator_index) = key_text.find(format.separator()) else {
+        return ExistingValue::default();
+    };
+    let prefix = key_text[..=separator_index].to_string();
+    let value_text = &key_text[separator_index + 1..];
+    let inline = value_text.lines().next().unwrap_or_default().trim();
+
+    if inline.starts_with('[') {
+        return parse_flow_list(prefix, value_text);
+    }
+
+    if inline.is_empty() || inline.starts_with('#') {
+        let mut indent = None;
+        let mut items = Vec::new();
+        for line in value_text.lines().skip(1) {
+            let trimmed = line.trim_start();
+            if let Some(item) = trimmed.strip_prefix('-') {
+                indent.get_or_insert_with(|| line[..line.len() - trimmed.len()].to_string());
+                let item = split_scalar(item).0;
+                if !item.is_empty() {
+                    items.push(item);
+                }
+            }
+        }
+        let list = indent.map(|indent| (items, ListStyle::Block(indent)));
+        return ExistingValue {
+            prefix,
+            list,
+            ..ExistingValue::default()
+        };
+    }
+
+    let (scalar, quote, comment) = split_scalar(inline);
+    ExistingValue {
+        prefix,
+        scalar: Some(scalar),
+        quote,
+        comment,
+        ..ExistingValue::default()
+    }
+}
+
+fn yaml_needs_quotes(text: &str) -> bool {
+    text.is_empty()
+        || text.trim() != text
+        || text.parse::<f64>().is_ok()
+        || matches!(
+            text.to_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~"
+        )
+        || text.starts_with([
+            '-', '?', ':', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@', '`',
+        ])
+        || text.ends_with(':')
+        || text.contains(": ")
+        || text.contains(" #")
+        // Flow indicators would break items of a `[a, b]` list
+        || text.contains([',', '[', ']', '{', '}', '\n', '\r', '\t'])
+}
+
+fn double_quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{escaped}\"")
+}
+
+fn format_string(format: FrontMatterFormat, text: &str, quote: Option<char>) -> String {
+    let is_single_line = !text.contains(['\n', '\r']);
+    match (format, quote) {
+        (FrontMatterFormat::Yaml, Some('\'')) if is_single_line => {
+            format!("'{}'", text.replace('\'', "''"))
+        }
+        (FrontMatterFormat::Toml, Some('\'')) if is_single_line && !text.contains('\'') => {
+            format!("'{text}'")
+        }
+        (FrontMatterFormat::Yaml, None) if !yaml_needs_quotes(text) => text.to_string(),
+        _ => double_quote(text),
+    }
+}
+
+fn default_prefix(format: FrontMatterFormat, key: &str) -> String {
+    if format == FrontMatterFormat::Toml {
+        format!("{key} =")
+    } else {
+        format!("{key}:")
+    }
+}
+
+fn format_key(
+    format: FrontMatterFormat,
+    existing: &ExistingValue,
+    value: &FrontMatterValue,
+    newline: &str,
+) -> String {
+    let prefix = &existing.prefix;
+    let comment = existing.comment.as_deref().unwrap_or_default();
+    let items = match value {
+        FrontMatterValue::String(text) => {
+            let formatted = format_string(format, text, existing.quote);
+            return format!("{prefix} {formatted}{comment}{newline}");
+        }
+        FrontMatterValue::Bool(flag) => return format!("{prefix} {flag}{comment}{newline}"),
+        FrontMatterValue::Integer(number) => return format!("{prefix} {number}{comment}{newline}"),
+        FrontMatterValue::List(items) => items,
+    };
+
+    let style = match (format, &existing.list) {
+        (FrontMatterFormat::Yaml, Some((_, style))) => style.clone(),
+        // New YAML lists follow the block style of the default note template
+        (FrontMatterFormat::Yaml, None) if existing.scalar.is_none() => {
+            ListStyle::Block("    ".to_string())
+        }
+        _ => ListStyle::Flow,
+    };
+    let formatted_items = items.iter().map(|item| format_string(format, item, None));
+    match style {
+        _ if items.is_empty() => format!("{prefix} []{comment}{newline}"),
+        ListStyle::Flow => {
+            let formatted_items: Vec<String> = formatted_items.collect();
+            format!(
+                "{prefix} [{}]{comment}{newline}",
+                formatted_items.join(", ")
+            )
+        }
+        ListStyle::Block(indent) => {
+            let mut output = format!("{prefix}{newline}");
+            for item in formatted_items {
+                let _ = write!(output, "{indent}- {item}{newline}");
+            }
+            output
+        }
+    }
+}
+
+fn is_unchanged(
+    format: FrontMatterFormat,
+    existing: &ExistingValue,
+    value: &FrontMatterValue,
+) -> bool {
+    match (value, &existing.scalar, &existing.list) {
+        (FrontMatterValue::List(items), _, Some((current, _))) => items == current,
+        // An unquoted `2024` is a number, so it changes when it has to be quoted
+        (FrontMatterValue::String(text), Some(current), _) => {
+            text == current
+                && (existing.quote.is_some() || format_string(format, text, None) == *current)
+        }
+        (FrontMatterValue::Bool(flag), Some(current), _) => {
+            existing.quote.is_none() && *current == flag.to_string()
+        }
+        (FrontMatterValue::Integer(number), Some(current), _) => {
+            existing.quote.is_none() && *current == number.to_string()
+        }
+        _ => false,
+    }
+}
+
+/// The indentation of the first key in a JSON object, or two spaces.
+fn json_indent(object_text: &str) -> String {
+    object_text
+        .lines()
+        .nth(1)
+        .map(|line| line[..line.len() - line.trim_start().len()].to_string())
+        .filter(|indent| !indent.is_empty())
+        .unwrap_or_else(|| "  ".to_string())
+}
+
+fn format_json(object: &Map<String, Value>, indent: &str, newline: &str) -> String {
+    let mut output = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+    serde::Serialize::serialize(object, &mut serializer)
+        .expect("serializing a JSON map cannot fail");
+    String::from_utf8(output)
+        .expect("serde_json always writes UTF-8")
+        .replace('\n', newline)
+}
+
+fn json_scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Bool(_) | Value::Number(_) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Edits the frontmatter of a raw markdown file.
+pub struct FrontMatterEditor {
+    raw: String,
+    default_format: FrontMatterFormat,
+}
+
+impl FrontMatterEditor {
+    pub fn new(raw: &str) -> Self {
+        FrontMatterEditor {
+            raw: raw.to_string(),
+            default_format: FrontMatterFormat::Yaml,
+        }
+    }
+
+    /// The format used when a frontmatter block has to be created.
+    #[must_use]
+    pub fn with_default_format(mut self, format: FrontMatterFormat) -> Self {
+        self.default_format = format;
+        self
+    }
+
+    pub fn format(&self) -> Option<FrontMatterFormat> {
+        find_frontmatter(&self.raw).map(|block| block.format)
+    }
+
+    fn existing(&self, key: &str) -> Option<ExistingValue> {
+        let block = find_frontmatter(&self.raw)?;
+        if block.format == FrontMatterFormat::Json {
+            let value = self.json_object(&block)?.remove(key)?;
+            let list = value.as_array().map(|items| {
+                let items = items.iter().filter_map(json_scalar).collect();
+                (items, ListStyle::Flow)
+            });
+            return Some(ExistingValue {
+                scalar: json_scalar(&value),
+                list,
+                ..ExistingValue::default()
+            });
+        }
+        let (start, end) = find_key(&self.raw, &block, key)?;
+        Some(parse_existing(block.format, &self.raw[start..end]))
+    }
+
+    fn json_object(&self, block: &FrontMatterBlock) -> Option<Map<String, Value>> {
+        serde_json::from_str(&self.raw[block.start..block.end]).ok()
+    }
+
+    fn update_json<F>(&mut self, block: &FrontMatterBlock, update: F)
+    where
+        F: FnOnce(&mut Map<String, Value>),
+    {
+        let Some(mut object) = self.json_object(block) else {
+            return;
+        };
+        let original = object.clone();
+        update(&mut object);
+        if object == original {
+            return;
+        }
+        let object_text = &self.raw[block.start..block.end];
+        let formatted = format_json(&object, &json_indent(object_text), line_ending(&self.raw));
+        self.raw.replace_range(block.start..block.end, &formatted);
+    }
+
+    /// The value of a scalar key, unquoted.
+    pub fn get_str(&self, key: &str) -> Option<String> {
+        self.existing(key)?.scalar
+    }
+
+    /// The items of a list key. A scalar value is split on commas.
+    #[cfg(test)]
+    pub fn get_list(&self, key: &str) -> Option<Vec<String>> {
+        let existing = self.existing(key)?;
+        if let Some((items, _)) = existing.list {
+            return Some(items);
+        }
+        let scalar = existing.scalar.unwrap_or_default();
+        Some(
+            scalar
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect(),
+        )
+    }
+
+    /// Set `key` to `value`, replacing only that key's text.
+    ///
+    /// New keys are appended to the frontmatter, which is created when missing.
+    pub fn set(&mut self, key: &str, value: impl Into<FrontMatterValue>) {
+        let value = value.into();
+        let newline = line_ending(&self.raw);
+
+        let Some(block) = find_frontmatter(&self.raw) else {
+            let format = self.default_format;
+            let (bom, body) = match self.raw.strip_prefix(BOM) {
+                Some(body) => (BOM, body),
+                None => ("", self.raw.as_str()),
+            };
+            if format == FrontMatterFormat::Json {
+                let mut object = Map::new();
+                object.insert(key.to_string(), Value::from(&value));
+                let formatted = format_json(&object, "  ", newline);
+                self.raw = format!("{bom}{formatted}{newline}{body}");
+                return;
+            }
+            let delimiter = format.delimiter();
+            let existing = ExistingValue {
+                prefix: default_prefix(format, key),
+                ..ExistingValue::default()
+            };
+            let key_text = format_key(format, &existing, &value, newline);
+            self.raw = format!("{bom}{delimiter}{newline}{key_text}{delimiter}{newline}{body}");
+            return;
+        };
+
+        if block.format == FrontMatterFormat::Json {
+            self.update_json(&block, |object| {
+                object.insert(key.to_string(), Value::from(&value));
+            });
+            return;
+        }
+
+        if let Some((start, end)) = find_key(&self.raw, &block, key) {
+            let existing = parse_existing(block.format, &self.raw[start..end]);
+            if is_unchanged(block.format, &existing, &value) {
+                return;
+            }
+            let key_text = format_key(block.format, &existing, &value, newline);
+            self.raw.replace_range(start..end, &key_text);
+        } else {
+            let existing = ExistingValue {
+                prefix: default_prefix(block.format, key),
+                ..ExistingValue::default()
+            };
+            let key_text = format_key(block.format, &existing, &value, newline);
+            let at = insertion_point(&self.raw, &block);
+            self.raw.insert_str(at, &key_text);
+        }
+    }
+
+    /// Remove `key` from the frontmatter. Returns whether the key was present.
+    pub fn remove(&mut self, key: &str) -> bool {
+        let Some(block) = find_frontmatter(&self.raw) else {
+            return false;
+        };
+        if block.format == FrontMatterFormat::Json {
+            let mut removed = false;
+            self.update_json(&block, |object| {
+                removed = object.shift_remove(key).is_some();
+            });
+            return removed;
+        }
+        let Some((start, end)) = find_key(&self.raw, &block, key) else {
+            return false;
+        };
+        self.raw.replace_range(start..end, "");
+        true
+    }
+
+    /// The markdown after the frontmatter block.
+    pub fn body(&self) -> &str {
+        let Some(block) = find_frontmatter(&self.raw) else {
+            return self.raw.strip_prefix(BOM).unwrap_or(&self.raw);
+        };
+        let closing = &self.raw[block.end..];
+        if block.format == FrontMatterFormat::Json {
+            return closing
+                .strip_prefix("\r\n")
+                .or_else(|| closing.strip_prefix('\n'))
+                .unwrap_or(closing);
+        }
+        closing.find('\n').map_or("", |i| &closing[i + 1..])
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn into_string(self) -> String {
+        self.raw
+    }
+}
+
+/// Number of lines taken up by frontmatter, so positions in the body can be
+/// reported as lines of the source file.
+pub fn frontmatter_line_count(raw: &str) -> usize {
+    let editor = FrontMatterEditor::new(raw);
+    if editor.format().is_none() {
+        return 0;
+    }
+    let header = &raw[..raw.len() - editor.body().len()];
+    header.matches('\n').count()
+}
+
+/// Read the tags from the frontmatter of a raw markdown file.
+///
+/// Unlike `markdown::frontmatter`, this tolerates a scalar `tags: foo bar` value.
+pub fn read_tags(raw: &str) -> Vec<String> {
+    let Some(existing) = FrontMatterEditor::new(raw).existing("tags") else {
+        return Vec::new();
+    };
+    match (existing.list, existing.scalar) {
+        (Some((items, _)), _) => items,
+        (None, Some(scalar)) => scalar
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect(),
+        (None, None) => Vec::new(),
+    }
+}
+
+/// Rewrite only the `tags:` key of the frontmatter, leaving every other byte untouched.
+///
+/// `update` receives the current tags and returns the new ones. A frontmatter
+/// block in `default_format` is added when the file has none, and the input is
+/// returned as-is when the tags do not change.
+pub fn update_tags<F>(raw: &str, default_format: FrontMatterFormat, update: F) -> String
+where
+    F: FnOnce(Vec<String>) -> Vec<String>,
+{
+    let tags = read_tags(raw);
+    let new_tags = update(tags.clone());
+    if new_tags == tags {
+        return raw.to_string();
+    }
+    let mut editor = FrontMatterEditor::new(raw).with_default_format(default_format);
+    editor.set("tags", new_tags);
+    editor.into_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add<'a>(tags: &'a [&'a str]) -> impl FnOnce(Vec<String>) -> Vec<String> + 'a {
+        move |mut existing| {
+            for tag in tags {
+                if !existing.iter().any(|t| t == tag) {
+                    existing.push((*tag).to_string());
+                }
+            }
+            existing
+        }
+    }
+
+    const MESSY_YAML: &str = "---\n# Imported from Obsidian\ntitle: 'Meeting: Q3'   # keep this comment\naliases:\n  - Q3 sync\n\n  - third quarter\ncreated: 2024-03-01\ntags: [work, \"planning\"]\nnested:\n  key: value\n---\n# Meeting\n\n--- not a delimiter\n";
+
+    #[test]
+    fn test_set_string_keeps_quote_style_and_comment() {
+        let mut editor = FrontMatterEditor::new(MESSY_YAML);
+        editor.set("title", "It's Q3");
+        assert_eq!(
+            editor.as_str(),
+            MESSY_YAML.replace(
+                "title: 'Meeting: Q3'   # keep this comment",
+                "title: 'It''s Q3'   # keep this comment"
+            )
+        );
+    }
+
+    #[test]
+    fn test_set_string_quotes_other_scalars() {
+        let mut editor = FrontMatterEditor::new("---\ntitle: 2024\ndraft: true\n---\n");
+        editor.set("title", "2024");
+        editor.set("draft", "true");
+        assert_eq!(
+            editor.as_str(),
+            "---\ntitle: \"2024\"\ndraft: \"true\"\n---\n"
+        );
+        let mut editor = FrontMatterEditor::new("+++\ntitle = 2024\n+++\n");
+        editor.set("title", "2024");
+        assert_eq!(editor.as_str(), "+++\ntitle = \"2024\"\n+++\n");
+    }
+
+    #[test]
+    fn test_get_values_from_messy_yaml() {
+        let editor = FrontMatterEditor::new(MESSY_YAML);
+        assert_eq!(editor.format(), Some(FrontMatterFormat::Yaml));
+        assert_eq!(editor.get_str("title").as_deref(), Some("Meeting: Q3"));
+        assert_eq!(editor.get_str("created").as_deref(), Some("2024-03-01"));
+        assert_eq!(
+            editor.get_list("aliases"),
+            Some(vec!["Q3 sync".to_string(), "third quarter".to_string()])
+        );
+        assert_eq!(
+            editor.get_list("tags"),
+            Some(vec!["work".to_string(), "planning".to_string()])
+        );
+        assert_eq!(editor.get_str("missing"), None);
+        assert_eq!(editor.body(), "# Meeting\n\n--- not a delimiter\n");
+    }
+
+    #[test]
+    fn test_set_list_in_block_style_with_blank_line() {
+        let mut editor = FrontMatterEditor::new(MESSY_YAML);
+        editor.set("aliases", vec!["Q3".to_string()]);
+        assert_eq!(
+            editor.as_str(),
+            MESSY_YAML.replace(
+                "aliases:\n  - Q3 sync\n\n  - third quarter\n",
+                "aliases:\n  - Q3\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_set_new_key_and_remove_key() {
+        let mut editor = FrontMatterEditor::new(MESSY_YAML);
+        editor.set("read", false);
+        assert!(editor.remove("nested"));
+        assert!(!editor.remove("nested"));
+        assert_eq!(
+            editor.as_str(),
+            MESSY_YAML.replace("nested:\n  key: value\n", "read: false\n")
+        );
+    }
+
+    #[test]
+    fn test_set_unchanged_value_is_byte_for_byte() {
+        let mut editor = FrontMatterEditor::new(MESSY_YAML);
+        editor.set("title", "Meeting: Q3");
+        editor.set("tags", vec!["work".to_string(), "planning".to_string()]);
+        assert_eq!(editor.as_str(), MESSY_YAML);
+    }
+
+    #[test]
+    fn test_no_frontmatter() {
+        let raw = "# Just a heading\n\nSome text.\n";
+        let editor = FrontMatterEditor::new(raw);
+        assert_eq!(editor.format(), None);
+        assert_eq!(editor.get_str("title"), None);
+        assert_eq!(editor.body(), raw);
+
+        let mut editor = FrontMatterEditor::new(raw);
+        assert!(!editor.remove("title"));
+        editor.set("title", "Just a heading");
+        assert_eq!(
+            editor.as_str(),
+            "---\ntitle: Just a heading\n---\n# Just a heading\n\nSome text.\n"
+        );
+    }
+
+    #[test]
+    fn test_no_frontmatter_uses_default_format() {
+        let mut editor =
+            FrontMatterEditor::new("body\n").with_default_format(FrontMatterFormat::Toml);
+        editor.set("title", "A \"quoted\" title");
+        assert_eq!(
+            editor.as_str(),
+            "+++\ntitle = \"A \\\"quoted\\\" title\"\n+++\nbody\n"
+        );
+    }
+
+    #[test]
+    fn test_unterminated_frontmatter_is_treated_as_body() {
+        let raw = "---\ntitle: never closed\n";
+        let editor = FrontMatterEditor::new(raw);
+        assert_eq!(editor.format(), None);
+        assert_eq!(editor.body(), raw);
+    }
+
+    const HUGO_TOML: &str = "+++\ntitle = 'Hugo Post'\ndate = 2023-01-01T00:00:00Z\ndraft = true # publish later\ntags = [\n  \"go\",\n  \"web\",\n]\n\n[params]\ntitle = \"nested title\"\n+++\nBody with = signs\n";
+
+    #[test]
+    fn test_toml_get_and_set() {
+        let mut editor = FrontMatterEditor::new(HUGO_TOML);
+        assert_eq!(editor.format(), Some(FrontMatterFormat::Toml));
+        assert_eq!(editor.get_str("title").as_deref(), Some("Hugo Post"));
+        assert_eq!(
+            editor.get_list("tags"),
+            Some(vec!["go".to_string(), "web".to_string()])
+        );
+
+        editor.set("draft", false);
+        editor.set("tags", vec!["go".to_string()]);
+        editor.set("title", "Renamed");
+        assert_eq!(
+            editor.as_str(),
+            "+++\ntitle = 'Renamed'\ndate = 2023-01-01T00:00:00Z\ndraft = false # publish later\ntags = [\"go\"]\n\n[params]\ntitle = \"nested title\"\n+++\nBody with = signs\n"
+        );
+    }
+
+    #[test]
+    fn test_toml_new_key_goes_before_tables() {
+        let mut editor = FrontMatterEditor::new(HUGO_TOML);
+        editor.set("url", "https://example.com");
+        assert!(editor
+            .as_str()
+            .contains("]\n\nurl = \"https://example.com\"\n[params]\n"));
+        assert_eq!(editor.body(), "Body with = signs\n");
+    }
+
+    #[test]
+    fn test_crlf_line_endings_are_preserved() {
+        let raw = "---\r\ntitle: Old\r\ntags:\r\n  - a\r\n---\r\nLine one\r\nLine two\r\n";
+        let mut editor = FrontMatterEditor::new(raw);
+        assert_eq!(editor.get_str("title").as_deref(), Some("Old"));
+        editor.set("title", "New");
+        editor.set("tags", vec!["a".to_string(), "b".to_string()]);
+        editor.set("url", "https://example.com");
+        assert_eq!(
+            editor.as_str(),
+            "---\r\ntitle: New\r\ntags:\r\n  - a\r\n  - b\r\nurl: https://example.com\r\n---\r\nLine one\r\nLine two\r\n"
+        );
+        assert_eq!(editor.body(), "Line one\r\nLine two\r\n");
+    }
+
+    #[test]
+    fn test_byte_order_mark_is_preserved() {
+        let raw = "\u{feff}---\ntitle: x\n---\nbody";
+        let mut editor = FrontMatterEditor::new(raw);
+        editor.set("title", "y");
+        assert_eq!(editor.as_str(), "\u{feff}---\ntitle: y\n---\nbody");
+    }
+
+    #[test]
+    fn test_yaml_document_end_marker() {
+        let raw = "---\ntitle: x\n...\nbody";
+        let mut editor = FrontMatterEditor::new(raw);
+        editor.set("url", "https://example.com");
+        assert_eq!(
+            editor.as_str(),
+            "---\ntitle: x\nurl: https://example.com\n...\nbody"
+        );
+    }
+
+    const HUGO_JSON: &str = "{\n    \"title\": \"JSON Post\",\n    \"draft\": true,\n    \"tags\": [\"a\"]\n}\n\n# Body {not json}\n";
+
+    #[test]
+    fn test_json_get_set_and_remove() {
+        let mut editor = FrontMatterEditor::new(HUGO_JSON);
+        assert_eq!(editor.format(), Some(FrontMatterFormat::Json));
+        assert_eq!(editor.get_str("title").as_deref(), Some("JSON Post"));
+        assert_eq!(editor.get_str("draft").as_deref(), Some("true"));
+        assert_eq!(editor.get_list("tags"), Some(vec!["a".to_string()]));
+        assert_eq!(editor.body(), "\n# Body {not json}\n");
+
+        editor.set("title", "JSON Post");
+        assert_eq!(editor.as_str(), HUGO_JSON);
+
+        editor.set("tags", vec!["a".to_string(), "b".to_string()]);
+        editor.set("url", "https://example.com");
+        assert!(editor.remove("draft"));
+        assert_eq!(
+            editor.as_str(),
+            "{\n    \"title\": \"JSON Post\",\n    \"tags\": [\n        \"a\",\n        \"b\"\n    ],\n    \"url\": \"https://example.com\"\n}\n\n# Body {not json}\n"
+        );
+    }
+
+    #[test]
+    fn test_json_frontmatter_created_when_missing() {
+        let mut editor =
+            FrontMatterEditor::new("body\n").with_default_format(FrontMatterFormat::Json);
+        editor.set("title", "New");
+        assert_eq!(editor.as_str(), "{\n  \"title\": \"New\"\n}\nbody\n");
+        assert_eq!(editor.body(), "body\n");
+    }
+
+    #[test]
+    fn test_detect_format() {
+        assert_eq!(detect_format(MESSY_YAML), Some(FrontMatterFormat::Yaml));
+        assert_eq!(detect_format(HUGO_TOML), Some(FrontMatterFormat::Toml));
+        assert_eq!(detect_format(HUGO_JSON), Some(FrontMatterFormat::Json));
+        assert_eq!(detect_format("{ not json"), None);
+        assert_eq!(detect_format("# Title"), None);
+    }
+
+    #[test]
+    fn test_update_block_tags_preserves_everything_else() {
+        let raw = "---\ntitle: \"A Note\"\ntags:\n  - rust\n  - cli\nurl: https://example.com\n---\n# Body\n\ntext\n";
+        let updated = update_tags(raw, FrontMatterFormat::Yaml, add(&["new"]));
+        assert_eq!(
+            updated,
+            "---\ntitle: \"A Note\"\ntags:\n  - rust\n  - cli\n  - new\nurl: https://example.com\n---\n# Body\n\ntext\n"
+        );
+    }
+
+    #[test]
+    fn test_update_flow_tags_keeps_flow_style() {
+        let raw = "---\ntags: [\"a\", 'b, c']\ntitle: x\n---\nbody";
+        let updated = update_tags(raw, FrontMatterFormat::Yaml, |tags| {
+            assert_eq!(tags, vec!["a".to_string(), "b, c".to_string()]);
+            vec!["a".to_string()]
+        });
+        assert_eq!(updated, "---\ntags: [a]\ntitle: x\n---\nbody");
+    }
+
+    #[test]
+    fn test_update_tags_adds_key_when_missing() {
+        let raw = "---\ntitle: x\n---\nbody";
+        let updated = update_tags(raw, FrontMatterFormat::Yaml, add(&["todo"]));
+        assert_eq!(updated, "---\ntitle: x\ntags:\n    - todo\n---\nbody");
+    }
+
+    #[test]
+    fn test_update_tags_adds_frontmatter_when_missing() {
+        let raw = "# Heading\n";
+        let updated = update_tags(raw, FrontMatterFormat::Yaml, add(&["todo"]));
+        assert_eq!(updated, "---\ntags:\n    - todo\n---\n# Heading\n");
+    }
+
+    #[test]
+    fn test_update_tags_scalar_and_crlf() {
+        let raw = "---\r\ntags: foo\r\n---\r\nbody\r\n";
+        let updated = update_tags(raw, FrontMatterFormat::Yaml, add(&["bar"]));
+        assert_eq!(updated, "---\r\ntags: [foo, bar]\r\n---\r\nbody\r\n");
+    }
+
+    #[test]
+    fn test_update_tags_quotes_when_needed() {
+        let raw = "---\ntags: []\n---\n";
+        let updated = update_tags(
+            raw,
+            FrontMatterFormat::Yaml,
+            add(&["2024", "a, b", "c#", "yes"]),
+        );
+        assert_eq!(
+            updated,
+            "---\ntags: [\"2024\", \"a, b\", c#, \"yes\"]\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_update_tags_unchanged_returns_input() {
+        let raw = "---\ntags: [\"a\"]\n---\n";
+        assert_eq!(update_tags(raw, FrontMatterFormat::Yaml, add(&["a"])), raw);
+        assert_eq!(
+            update_tags("body", FrontMatterFormat::Yaml, |tags| tags),
+            "body"
+        );
+    }
+
+    #[test]
+    fn test_frontmatter_line_count() {
+        assert_eq!(frontmatter_line_count("---\ntitle: a\n---\n\nBody\n"), 3);
+        assert_eq!(frontmatter_line_count("+++\ntitle = \"a\"\n+++\nBody\n"), 3);
+        assert_eq!(
+            frontmatter_line_count("{\n  \"title\": \"a\"\n}\nBody\n"),
+            3
+        );
+        assert_eq!(frontmatter_line_count("# Heading\n"), 0);
+    }
+
+    #[test]
+    fn test_read_tags() {
+        assert_eq!(
+            read_tags("---\ntags:\n- a\n- \"b\"\n---\n"),
+            vec!["a".to_string(), "b".to_string()]
+        );
+        assert_eq!(
+            read_tags("---\ntags: foo bar, baz\n---\n"),
+            vec!["foo".to_string(), "bar".to_string(), "baz".to_string()]
+        );
+        assert!(read_tags("no frontmatter").is_empty());
+    }
+}
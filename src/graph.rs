@@ -0,0 +1,320 @@
+use crate::cli::GraphFormat;
+use crate::error::InkError;
+use crate::links::{normalize_path, note_id, scan_vault, LinkResolver, LinkedNote};
+use crate::search::find_notes;
+use crate::settings::SETTINGS;
+use crate::utils::escape_xml;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize)]
+struct GraphNode {
+    id: String,
+    title: String,
+    tags: Vec<String>,
+    path: PathBuf,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum EdgeKind {
+    Link,
+    Tag,
+}
+
+#[derive(Debug, Serialize)]
+struct GraphEdge {
+    source: String,
+    target: String,
+    kind: EdgeKind,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Graph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+fn build_graph(
+    notes_root: &Path,
+    linked_notes: &[LinkedNote],
+    resolver: &LinkResolver,
+    include_tag_edges: bool,
+) -> Graph {
+    let ids: HashMap<&Path, String> = linked_notes
+        .iter()
+        .map(|linked_note| {
+            (
+                linked_note.path.as_path(),
+                note_id(notes_root, &linked_note.path),
+            )
+        })
+        .collect();
+
+    let nodes = linked_notes
+        .iter()
+        .map(|linked_note| {
+            let mut tags: Vec<String> = linked_note.note.tags.iter().cloned().collect();
+            tags.sort();
+            GraphNode {
+                id: ids[linked_note.path.as_path()].clone(),
+                title: linked_note.note.title.clone(),
+                tags,
+                path: linked_note.path.clone(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut edges = Vec::new();
+    let mut seen_links: HashSet<(String, String)> = HashSet::new();
+    for linked_note in linked_notes {
+        let source = &ids[linked_note.path.as_path()];
+        for link in &linked_note.links {
+            let Some(target_path) = resolver.resolve(&linked_note.path, link) else {
+                continue;
+            };
+            let Some(target) = ids.get(target_path.as_path()) else {
+                continue;
+            };
+            if target == source || !seen_links.insert((source.clone(), target.clone())) {
+                continue;
+            }
+            edges.push(GraphEdge {
+                source: source.clone(),
+                target: target.clone(),
+                kind: EdgeKind::Link,
+                tags: Vec::new(),
+            });
+        }
+    }
+
+    if include_tag_edges {
+        // Group notes by tag first, so only notes that share a tag are paired up
+        let mut notes_by_tag: BTreeMap<&String, Vec<usize>> = BTreeMap::new();
+        for (index, node) in nodes.iter().enumerate() {
+            for tag in &node.tags {
+                notes_by_tag.entry(tag).or_default().push(index);
+            }
+        }
+        let mut shared_tags: BTreeMap<(usize, usize), Vec<String>> = BTreeMap::new();
+        for (tag, indexes) in notes_by_tag {
+            for (i, &a) in indexes.iter().enumerate() {
+                for &b in &indexes[i + 1..] {
+                    shared_tags.entry((a, b)).or_default().push(tag.clone());
+                }
+            }
+        }
+        for ((a, b), tags) in shared_tags {
+            edges.push(GraphEdge {
+                source: nodes[a].id.clone(),
+                target: nodes[b].id.clone(),
+                kind: EdgeKind::Tag,
+                tags,
+            });
+        }
+    }
+
+    Graph { nodes, edges }
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn to_dot(graph: &Graph) -> String {
+    let mut output = String::from("digraph ink {\n");
+    for node in &graph.nodes {
+        let _ = writeln!(
+            output,
+            "  \"{}\" [label=\"{}\", tags=\"{}\"];",
+            escape_dot(&node.id),
+            escape_dot(&node.title),
+            escape_dot(&node.tags.join(","))
+        );
+    }
+    for edge in &graph.edges {
+        match edge.kind {
+            EdgeKind::Link => {
+                let _ = writeln!(
+                    output,
+                    "  \"{}\" -> \"{}\";",
+                    escape_dot(&edge.source),
+                    escape_dot(&edge.target)
+                );
+            }
+            EdgeKind::Tag => {
+                let _ = writeln!(
+                    output,
+                    "  \"{}\" -> \"{}\" [dir=none, style=dashed, label=\"{}\"];",
+                    escape_dot(&edge.source),
+                    escape_dot(&edge.target),
+                    escape_dot(&edge.tags.join(","))
+                );
+            }
+        }
+    }
+    output.push_str("}\n");
+    output
+}
+
+fn to_graphml(graph: &Graph) -> String {
+    let mut output = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        "  <key id=\"title\" for=\"node\" attr.name=\"title\" attr.type=\"string\"/>\n",
+        "  <key id=\"tags\" for=\"node\" attr.name=\"tags\" attr.type=\"string\"/>\n",
+        "  <key id=\"path\" for=\"node\" attr.name=\"path\" attr.type=\"string\"/>\n",
+        "  <key id=\"kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+        "  <key id=\"shared_tags\" for=\"edge\" attr.name=\"shared_tags\" attr.type=\"string\"/>\n",
+        "  <graph id=\"ink\" edgedefault=\"directed\">\n",
+    ));
+    for node in &graph.nodes {
+        let _ = write!(
+            output,
+            concat!(
+                "    <node id=\"{}\">\n",
+                "      <data key=\"title\">{}</data>\n",
+                "      <data key=\"tags\">{}</data>\n",
+                "      <data key=\"path\">{}</data>\n",
+                "    </node>\n",
+            ),
+            escape_xml(&node.id),
+            escape_xml(&node.title),
+            escape_xml(&node.tags.join(",")),
+            escape_xml(&node.path.to_string_lossy()),
+        );
+    }
+    for (i, edge) in graph.edges.iter().enumerate() {
+        let (kind, directed) = match edge.kind {
+            EdgeKind::Link => ("link", "true"),
+            EdgeKind::Tag => ("tag", "false"),
+        };
+        let _ = write!(
+            output,
+            concat!(
+                "    <edge id=\"e{}\" source=\"{}\" target=\"{}\" directed=\"{}\">\n",
+                "      <data key=\"kind\">{}</data>\n",
+                "      <data key=\"shared_tags\">{}</data>\n",
+                "    </edge>\n",
+            ),
+            i,
+            escape_xml(&edge.source),
+            escape_xml(&edge.target),
+            directed,
+            kind,
+            escape_xml(&edge.tags.join(",")),
+        );
+    }
+    output.push_str("  </graph>\n</graphml>\n");
+    output
+}
+
+/// Print the note graph, optionally limited to the notes matching a search query.
+pub fn graph(
+    format: GraphFormat,
+    query: Option<&str>,
+    include_ignored: bool,
+    include_tag_edges: bool,
+) -> Result<(), InkError> {
+    let notes_root = normalize_path(&SETTINGS.get_notes_path());
+    let (mut linked_notes, resolver) = scan_vault(&notes_root)?;
+
+    if let Some(query) = query {
+        let matching_paths: HashSet<PathBuf> =
+            find_notes(query, None, usize::MAX, include_ignored)?
+                .iter()
+                .map(|note| normalize_path(&note.get_file_path()))
+                .collect();
+        linked_notes.retain(|linked_note| matching_paths.contains(&linked_note.path));
+    } else if !include_ignored {
+        linked_notes.retain(|linked_note| !linked_note.note.is_hidden());
+    }
+    linked_notes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let graph = build_graph(&notes_root, &linked_notes, &resolver, include_tag_edges);
+
+    match format {
+        GraphFormat::Dot => print!("{}", to_dot(&graph)),
+        GraphFormat::Graphml => print!("{}", to_graphml(&graph)),
+        GraphFormat::Json => println!("{}", serde_json::to_string(&graph).unwrap()),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::links::extract_links;
+    use crate::note::Note;
+
+    fn linked_note(path: &str, title: &str, tags: &[&str], markdown: &str) -> LinkedNote {
+        let mut note = Note::new(title.to_string(), None);
+        for tag in tags {
+            note.add_tag((*tag).to_string());
+        }
+        LinkedNote {
+            path: PathBuf::from(path),
+            note,
+            links: extract_links(markdown),
+        }
+    }
+
+    fn sample_graph(include_tag_edges: bool) -> Graph {
+        let root = PathBuf::from("/notes");
+        let linked_notes = vec![
+            linked_note(
+                "/notes/a.md",
+                "Note \"A\"",
+                &["rust", "cli"],
+                "[[b]] [[b]] [[a]]",
+            ),
+            linked_note("/notes/sub/b.md", "B & co", &["rust"], "[[missing]]"),
+        ];
+        let paths: Vec<PathBuf> = linked_notes.iter().map(|n| n.path.clone()).collect();
+        let resolver = LinkResolver::new(&root, &paths);
+        build_graph(&root, &linked_notes, &resolver, include_tag_edges)
+    }
+
+    #[test]
+    fn test_build_graph_dedupes_link_edges_and_skips_self_links() {
+        let graph = sample_graph(false);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(graph.nodes[1].id, "sub/b");
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].source, "a");
+        assert_eq!(graph.edges[0].target, "sub/b");
+        assert_eq!(graph.edges[0].kind, EdgeKind::Link);
+    }
+
+    #[test]
+    fn test_build_graph_shared_tag_edges() {
+        let graph = sample_graph(true);
+        let tag_edges: Vec<&GraphEdge> = graph
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == EdgeKind::Tag)
+            .collect();
+        assert_eq!(tag_edges.len(), 1);
+        assert_eq!(tag_edges[0].tags, vec!["rust".to_string()]);
+    }
+
+    #[test]
+    fn test_to_dot_escapes_labels() {
+        let dot = to_dot(&sample_graph(true));
+        assert!(dot.starts_with("digraph ink {"));
+        assert!(dot.contains(r#""a" [label="Note \"A\"", tags="cli,rust"];"#));
+        assert!(dot.contains(r#""a" -> "sub/b";"#));
+        assert!(dot.contains("dir=none"));
+    }
+
+    #[test]
+    fn test_to_graphml_escapes_xml() {
+        let graphml = to_graphml(&sample_graph(false));
+        assert!(graphml.contains("<data key=\"title\">B &amp; co</data>"));
+        assert!(graphml.contains("source=\"a\" target=\"sub/b\" directed=\"true\""));
+    }
+}
@@ -0,0 +1,208 @@
+//! Fetching web pages for bookmarks.
+//!
+//! Every networked feature goes through the client built here from the
+//! `[http]` settings, so timeouts, the user agent, the proxy and offline mode
+//! apply everywhere.
+use crate::settings::{HttpSettings, SETTINGS};
+use reqwest::blocking::{Client, ClientBuilder};
+use reqwest::redirect::Policy;
+use reqwest::Url;
+use std::fmt;
+use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Turn off network access for the rest of the run, as with `--offline`.
+pub fn set_offline() {
+    OFFLINE.store(true, Ordering::Relaxed);
+}
+
+fn is_offline() -> bool {
+    SETTINGS.http.offline || OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Why a page could not be fetched.
+#[derive(Debug)]
+pub enum FetchError {
+    /// Network access is turned off
+    Offline,
+    /// The `[http]` settings do not make a valid client
+    Config(String),
+    Request(reqwest::Error),
+    /// The connection failed while reading the body
+    Body(std::io::Error),
+    /// The body is larger than `max_body_size`
+    TooLarge(u64),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FetchError::Offline => write!(f, "offline mode is on"),
+            FetchError::Config(message) => write!(f, "invalid [http] settings, {message}"),
+            FetchError::Request(e) => write!(f, "{e}"),
+            FetchError::Body(e) => write!(f, "could not read the page, {e}"),
+            FetchError::TooLarge(limit) => write!(f, "the page is larger than {limit} bytes"),
+        }
+    }
+}
+
+impl std::error::Error for FetchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FetchError::Request(e) => Some(e),
+            FetchError::Body(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> Self {
+        FetchError::Request(e)
+    }
+}
+
+/// A fetched page, with the URL it ended up at after redirects.
+pub struct FetchedPage {
+    pub url: Url,
+    pub body: String,
+}
+
+/// A client builder with the user agent, timeout and proxy from `settings`.
+fn client_builder(settings: &HttpSettings) -> Result<ClientBuilder, FetchError> {
+    let mut builder = Client::builder()
+        .user_agent(settings.user_agent.as_str())
+        .timeout(Duration::from_secs(settings.timeout));
+    if let Some(proxy) = &settings.proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|e| FetchError::Config(format!("proxy {proxy}: {e}")))?;
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder)
+}
+
+type SharedClient = OnceLock<Result<Client, String>>;
+
+/// The client in `shared`, built with the `redirect` policy on first use.
+fn shared_client(
+    shared: &'static SharedClient,
+    redirect: impl FnOnce() -> Policy,
+) -> Result<&'static Client, FetchError> {
+    if is_offline() {
+        return Err(FetchError::Offline);
+    }
+    shared
+        .get_or_init(|| {
+            client_builder(&SETTINGS.http)
+                .map_err(|e| e.to_string())?
+                .redirect(redirect())
+                .build()
+                .map_err(|e| e.to_string())
+        })
+        .as_ref()
+        .map_err(|message| FetchError::Config(message.clone()))
+}
+
+/// The client shared by every page fetch, built on first use.
+fn client() -> Result<&'static Client, FetchError> {
+    static CLIENT: SharedClient = OnceLock::new();
+    shared_client(&CLIENT, || Policy::limited(SETTINGS.http.redirect_limit))
+}
+
+/// Read at most `limit` bytes of a response body.
+fn read_body(response: reqwest::blocking::Response, limit: u64) -> Result<String, FetchError> {
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit)
+    {
+        return Err(FetchError::TooLarge(limit));
+    }
+    let mut body = Vec::new();
+    response
+        .take(limit + 1)
+        .read_to_end(&mut body)
+        .map_err(FetchError::Body)?;
+    if body.len() as u64 > limit {
+        return Err(FetchError::TooLarge(limit));
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// GET a page, treating error statuses as failures.
+pub fn fetch_page(url: &str) -> Result<FetchedPage, FetchError> {
+    let response = client()?.get(url).send()?.error_for_status()?;
+    let url = response.url().clone();
+    let body = read_body(response, SETTINGS.http.max_body_size)?;
+    Ok(FetchedPage { url, body })
+}
+
+/// The outcome of checking that a URL still works.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UrlStatus {
+    /// A success, or a temporary redirect
+    Ok(u16),
+    /// A 301 or 308 redirect to `location`
+    Moved { code: u16, location: String },
+    /// A 4xx or 5xx response
+    Broken(u16),
+    /// The host name does not resolve
+    DnsFailure(String),
+    /// Timeouts, refused connections and other failures
+    Failed(String),
+}
+
+/// The client for link checking, built on first use.
+///
+/// It reports redirects rather than following them, so `redirect_limit` does
+/// not apply, and the timeout is set on each request by `check_url`.
+pub fn checking_client() -> Result<&'static Client, FetchError> {
+    static CHECKING_CLIENT: SharedClient = OnceLock::new();
+    shared_client(&CHECKING_CLIENT, Policy::none)
+}
+
+fn classify(url: &Url, response: &reqwest::blocking::Response) -> UrlStatus {
+    let status = response.status();
+    let code = status.as_u16();
+    if status.is_redirection() {
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| url.join(location).ok());
+        return match location {
+            Some(location) if matches!(code, 301 | 308) => UrlStatus::Moved {
+                code,
+                location: location.to_string(),
+            },
+            _ => UrlStatus::Ok(code),
+        };
+    }
+    if status.is_client_error() || status.is_server_error() {
+        return UrlStatus::Broken(code);
+    }
+    UrlStatus::Ok(code)
+}
+
+/// Check a URL with a HEAD request, falling back to GET for servers that reject HEAD.
+pub fn check_url(client: &Client, url: &str, timeout: Duration) -> UrlStatus {
+    let Ok(parsed) = Url::parse(url) else {
+        return UrlStatus::Failed(format!("invalid url {url}"));
+    };
+    let status = match client.head(parsed.clone()).timeout(timeout).send() {
+        Ok(response) => classify(&parsed, &response),
+        Err(e) if e.is_dns() => return UrlStatus::DnsFailure(e.to_string()),
+        Err(e) => UrlStatus::Failed(e.to_string()),
+    };
+    if matches!(status, UrlStatus::Ok(_) | UrlStatus::Moved { .. }) {
+        return status;
+    }
+    match client.get(parsed.clone()).timeout(timeout).send() {
+        Ok(response) => classify(&parsed, &response),
+        Err(e) if e.is_dns() => UrlStatus::DnsFailure(e.to_string()),
+        Err(e) => UrlStatus::Failed(e.to_string()),
+    }
+}
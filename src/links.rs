@@ -0,0 +1,302 @@
+use crate::error::InkError;
+use crate::frontmatter::{frontmatter_line_count, FrontMatterEditor};
+use crate::markdown::{get_markdown_str_read_only, parse_body};
+use crate::note::Note;
+use crate::walk::{has_extension, walk_files};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkKind {
+    Wiki,
+    Markdown,
+}
+
+/// An internal link found in a note, pointing at another note in the vault.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Link {
+    /// The note part of the link, without any `#heading` or `|alias`
+    pub target: String,
+    /// 1-based line number within the file
+    pub line: usize,
+    pub kind: LinkKind,
+}
+
+fn has_non_markdown_extension(target: &str) -> bool {
+    Path::new(target)
+        .extension()
+        .is_some_and(|ext| !ext.eq_ignore_ascii_case("md"))
+}
+
+fn is_external(target: &str) -> bool {
+    target.contains("://") || target.starts_with("mailto:") || target.starts_with('#')
+}
+
+/// The note a `[[target#heading|alias]]` wikilink points at, skipping attachments.
+pub fn wiki_target(inner: &str) -> Option<String> {
+    let target = inner.split('|').next()?.split('#').next()?.trim();
+    if target.is_empty() || has_non_markdown_extension(target) {
+        return None;
+    }
+    Some(target.to_string())
+}
+
+/// The note a relative `.md` link points at, skipping external links and other files.
+pub fn markdown_target(href: &str) -> Option<String> {
+    if is_external(href) {
+        return None;
+    }
+    let target = href.split('#').next()?;
+    let is_markdown = Path::new(target)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+    if !is_markdown {
+        return None;
+    }
+    Some(percent_decode(target))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or_default();
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Extract wikilinks and relative `.md` links from a note body.
+///
+/// Links are taken from the parsed markdown, so code blocks and inline code are skipped.
+pub fn extract_links(body: &str) -> Vec<Link> {
+    parse_body(body)
+        .links
+        .into_iter()
+        .filter_map(|link| {
+            let (target, kind) = if link.is_wiki {
+                (wiki_target(&link.dest)?, LinkKind::Wiki)
+            } else {
+                (markdown_target(&link.dest)?, LinkKind::Markdown)
+            };
+            Some(Link {
+                target,
+                line: link.line,
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Lexically normalize a path, resolving `.` and `..` without touching the filesystem.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// A note's path relative to the notes folder, without the extension.
+pub fn note_id(notes_root: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(notes_root).unwrap_or(path);
+    relative
+        .with_extension("")
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Resolves link targets to note paths within the vault.
+pub struct LinkResolver {
+    notes_root: PathBuf,
+    paths: HashSet<PathBuf>,
+    by_id: HashMap<String, PathBuf>,
+}
+
+impl LinkResolver {
+    pub fn new(notes_root: &Path, note_paths: &[PathBuf]) -> Self {
+        let mut by_id = HashMap::new();
+        let mut paths = HashSet::new();
+        for path in note_paths {
+            let normalized = normalize_path(path);
+            if let Some(stem) = normalized.file_stem().map(|stem| stem.to_string_lossy()) {
+                // The first note found wins when several share an id
+                by_id
+                    .entry(stem.to_lowercase())
+                    .or_insert(normalized.clone());
+            }
+            paths.insert(normalized);
+        }
+        LinkResolver {
+            notes_root: normalize_path(notes_root),
+            paths,
+            by_id,
+        }
+    }
+
+    pub fn resolve(&self, source: &Path, link: &Link) -> Option<PathBuf> {
+        match link.kind {
+            LinkKind::Wiki => self.resolve_wiki(&link.target),
+            LinkKind::Markdown => {
+                let base = source.parent().unwrap_or(&self.notes_root);
+                let candidate = normalize_path(&base.join(&link.target));
+                self.paths.get(&candidate).cloned()
+            }
+        }
+    }
+
+    fn resolve_wiki(&self, target: &str) -> Option<PathBuf> {
+        let target = target.strip_suffix(".md").unwrap_or(target);
+        if target.contains('/') {
+            let candidate = normalize_path(&self.notes_root.join(format!("{target}.md")));
+            if let Some(path) = self.paths.get(&candidate) {
+                return Some(path.clone());
+            }
+        }
+        let id = Path::new(target).file_name()?.to_str()?.to_lowercase();
+        self.by_id.get(&id).cloned()
+    }
+}
+
+/// A note in the vault along with the internal links found in it.
+pub struct LinkedNote {
+    pub path: PathBuf,
+    pub note: Note,
+    pub links: Vec<Link>,
+}
+
+/// Read every markdown file under `notes_root` and extract its links.
+pub fn scan_vault(notes_root: &Path) -> Result<(Vec<LinkedNote>, LinkResolver), InkError> {
+    let mut linked_notes = Vec::new();
+    walk_files(notes_root, true, has_extension, |path| {
+        let raw_markdown = get_markdown_str_read_only(path)?;
+        let note = Note::from_markdown(path, &raw_markdown);
+        // Parse the body as it is in the file, so link lines only need the
+        // frontmatter lines added to match the source
+        let mut links = extract_links(FrontMatterEditor::new(&raw_markdown).body());
+        let frontmatter_lines = frontmatter_line_count(&raw_markdown);
+        for link in &mut links {
+            link.line += frontmatter_lines;
+        }
+        linked_notes.push(LinkedNote {
+            path: normalize_path(path),
+            note,
+            links,
+        });
+        Ok(())
+    })?;
+    let paths: Vec<PathBuf> = linked_notes.iter().map(|n| n.path.clone()).collect();
+    let resolver = LinkResolver::new(notes_root, &paths);
+    Ok((linked_notes, resolver))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(markdown: &str) -> Vec<(String, usize, LinkKind)> {
+        extract_links(markdown)
+            .into_iter()
+            .map(|link| (link.target, link.line, link.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_wikilinks_with_alias_and_heading() {
+        let links = targets("See [[Other Note]] and [[project/plan#Goals|the plan]].");
+        assert_eq!(
+            links,
+            vec![
+                ("Other Note".to_string(), 1, LinkKind::Wiki),
+                ("project/plan".to_string(), 1, LinkKind::Wiki),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_relative_markdown_links_only() {
+        let markdown = "[a](notes/a.md)\n[b](https://example.com/b.md)\n![img](pic.png)\n[c](../c.md#top)\n[d](#local)";
+        let links = targets(markdown);
+        assert_eq!(
+            links,
+            vec![
+                ("notes/a.md".to_string(), 1, LinkKind::Markdown),
+                ("../c.md".to_string(), 4, LinkKind::Markdown),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_links_skips_code() {
+        let markdown = "```\n[[in-fence]]\n```\n`[[inline]]` [[real]]";
+        assert_eq!(
+            targets(markdown),
+            vec![("real".to_string(), 4, LinkKind::Wiki)]
+        );
+    }
+
+    #[test]
+    fn test_extract_links_skips_attachments_and_self_links() {
+        assert!(targets("![[diagram.png]] [[#Heading]]").is_empty());
+    }
+
+    #[test]
+    fn test_extract_links_decodes_percent_escapes() {
+        let links = targets("[x](my%20note.md)");
+        assert_eq!(links[0].0, "my note.md");
+    }
+
+    #[test]
+    fn test_resolver() {
+        let root = PathBuf::from("/notes");
+        let paths = vec![
+            PathBuf::from("/notes/index.md"),
+            PathBuf::from("/notes/project/plan.md"),
+        ];
+        let resolver = LinkResolver::new(&root, &paths);
+        let source = Path::new("/notes/project/plan.md");
+
+        let wiki = |target: &str| Link {
+            target: target.to_string(),
+            line: 1,
+            kind: LinkKind::Wiki,
+        };
+        let markdown = |target: &str| Link {
+            target: target.to_string(),
+            line: 1,
+            kind: LinkKind::Markdown,
+        };
+
+        assert_eq!(
+            resolver.resolve(source, &wiki("Index")),
+            Some(PathBuf::from("/notes/index.md"))
+        );
+        assert_eq!(
+            resolver.resolve(source, &wiki("project/plan")),
+            Some(PathBuf::from("/notes/project/plan.md"))
+        );
+        assert_eq!(
+            resolver.resolve(source, &markdown("../index.md")),
+            Some(PathBuf::from("/notes/index.md"))
+        );
+        assert_eq!(resolver.resolve(source, &markdown("index.md")), None);
+        assert_eq!(resolver.resolve(source, &wiki("missing")), None);
+    }
+}
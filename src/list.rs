@@ -1,39 +1,38 @@
-use crate::markdown::{frontmatter, get_markdown_str};
+use crate::error::InkError;
+use crate::markdown::{frontmatter, get_markdown_str_read_only, heading_title};
 use crate::settings::SETTINGS;
 use crate::walk::{has_extension, walk_files};
-use walkdir::DirEntry;
 
-fn tag_matches(entry: &DirEntry, target_tags: &[String]) -> bool {
+fn tag_matches(note_tags: Option<&[String]>, target_tags: &[String]) -> bool {
     if target_tags.is_empty() {
         return true;
     }
-
-    let Some(path_str) = entry.path().to_str() else {
-        return false;
-    };
-    let raw_markdown = get_markdown_str(path_str);
-    let front_matter = frontmatter(&raw_markdown);
-    if let Some(tags) = front_matter.tags {
-        return tags.iter().any(|tag| target_tags.contains(tag));
-    }
-    false
+    note_tags.is_some_and(|tags| tags.iter().any(|tag| target_tags.contains(tag)))
 }
 
-pub fn list(recurse_into: bool, tags: &[String], _include_ignored: bool) {
+pub fn list(recurse_into: bool, tags: &[String], _include_ignored: bool) -> Result<(), InkError> {
     // TODO: Implement include_ignored functionality for list
     // For now, just use the original implementation
     walk_files(
         &SETTINGS.get_notes_path(),
         recurse_into,
-        |entry| has_extension(entry) && tag_matches(entry, tags),
-        |path_str| {
-            let raw_markdown = get_markdown_str(path_str);
+        has_extension,
+        |path| {
+            let raw_markdown = get_markdown_str_read_only(path)?;
             let front_matter = frontmatter(&raw_markdown);
-            if let Some(title) = front_matter.title {
+            if !tag_matches(front_matter.tags.as_deref(), tags) {
+                return Ok(());
+            }
+            let title = front_matter
+                .title
+                .or_else(|| heading_title(&front_matter.content, SETTINGS.title_heading_level));
+            let path_str = path.display();
+            if let Some(title) = title {
                 println!("{title}\t{path_str}");
             } else {
                 println!("{path_str}\t{path_str}");
             }
+            Ok(())
         },
-    );
+    )
 }
@@ -1,11 +1,32 @@
+mod archive;
+mod bookmark_check;
+mod bookmark_dedupe;
+mod bookmark_export;
+mod bookmark_import;
+mod bookmark_queue;
 mod bookmarks;
+mod capture;
+mod check;
 mod cli;
+mod doctor;
+mod editor;
+mod error;
+mod frontmatter;
+mod graph;
+mod http;
+mod links;
 mod list;
 mod markdown;
 mod note;
+mod page;
 mod prompt;
+mod resolve;
 mod search;
 mod settings;
+mod show;
+mod site;
+mod tags;
+mod tasks;
 mod template;
 mod utils;
 mod walk;
@@ -14,5 +35,7 @@ mod write;
 use crate::cli::run_cli;
 
 fn main() {
-    run_cli();
+    if let Err(e) = run_cli() {
+        e.exit();
+    }
 }
@@ -1,58 +1,581 @@
-use gray_matter::engine::YAML;
+use crate::error::InkError;
+use crate::frontmatter::{detect_format, FrontMatterFormat};
+use crate::page::PageMetadata;
+use crate::settings::SETTINGS;
+use chrono::{DateTime, NaiveDate, Utc};
+use gray_matter::engine::{Engine, TOML, YAML};
 use gray_matter::Matter;
-use serde::Deserialize;
+use pulldown_cmark::{
+    html, CodeBlockKind, Event, HeadingLevel, LinkType, Options, Parser, Tag, TagEnd,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
 use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+/// Deserialize an optional frontmatter value, treating one of the wrong type as
+/// missing so a stray `author: [Ada, Bob]` does not lose the rest of the note.
+pub fn ignore_wrong_type<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(T::deserialize(value).ok())
+}
 
 #[derive(Deserialize, Debug)]
 pub struct NoteFrontMatter {
     pub title: Option<String>,
     pub tags: Option<Vec<String>>,
     pub url: Option<String>,
+    pub created: Option<String>,
+    #[serde(default, deserialize_with = "ignore_wrong_type")]
+    pub read: Option<bool>,
+    #[serde(flatten)]
+    pub page: PageMetadata,
 }
 
 pub struct ParsedMarkdown {
     pub title: Option<String>,
     pub url: Option<String>,
     pub tags: Option<Vec<String>>,
+    pub created: Option<DateTime<Utc>>,
+    /// Whether a bookmark queued to read later has been read
+    pub read: Option<bool>,
+    /// Bookmark metadata
+    pub page: PageMetadata,
     pub content: String,
 }
 
-pub fn get_markdown_str(markdown_source: &str) -> String {
-    fs::read_to_string(markdown_source).expect("Something went wrong reading the file")
+/// A heading in a note body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heading {
+    pub level: u8,
+    pub text: String,
+}
+
+/// A fenced or indented code block, with the language from its info string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeBlock {
+    pub lang: Option<String>,
+    pub code: String,
+}
+
+/// A link in a note body, with the 1-based line it starts on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BodyLink {
+    pub dest: String,
+    pub is_wiki: bool,
+    pub line: usize,
+}
+
+/// The searchable parts of a note body, split out of the markdown AST.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MarkdownBody {
+    /// Prose only, without code blocks, HTML, link URLs or image alt text
+    pub text: String,
+    pub code_blocks: Vec<CodeBlock>,
+    /// Inline code spans, which also stay in `text`
+    pub inline_code: Vec<String>,
+    pub headings: Vec<Heading>,
+    /// Link destinations, including wikilink targets
+    pub links: Vec<BodyLink>,
+    pub image_alt: Vec<String>,
 }
 
-/*
-pub fn markdown(markdown_input: &str) -> Result<String, Box<dyn std::error::Error>> {
-    // Set up options and parser. Strikethroughs are not part of the CommonMark standard
-    // and we therefore must enable it explicitly.
+pub fn parser_options() -> Options {
+    // Strikethroughs and the rest are not part of the CommonMark standard
+    // and we therefore must enable them explicitly.
     let mut options = Options::empty();
     options.insert(Options::ENABLE_STRIKETHROUGH);
-    let parser = Parser::new_ext(&markdown_input, options);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_FOOTNOTES);
+    options.insert(Options::ENABLE_WIKILINKS);
+    options
+}
+
+fn push_separated(buffer: &mut String, text: &str) {
+    if !buffer.is_empty() && !buffer.ends_with(char::is_whitespace) {
+        buffer.push(' ');
+    }
+    buffer.push_str(text);
+}
+
+/// Parse a markdown body (without frontmatter) into its searchable parts.
+pub fn parse_body(content: &str) -> MarkdownBody {
+    let mut body = MarkdownBody::default();
+    let mut heading: Option<(u8, String)> = None;
+    let mut code_block: Option<CodeBlock> = None;
+    let mut image_alt: Option<String> = None;
+    let mut line = 1;
+    let mut scanned = 0;
+
+    for (event, range) in Parser::new_ext(content, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                heading = Some((level as u8, String::new()));
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, text)) = heading.take() {
+                    let text = text.trim().to_string();
+                    if !text.is_empty() {
+                        body.headings.push(Heading { level, text });
+                    }
+                }
+                body.text.push('\n');
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().map(str::to_lowercase)
+                    }
+                    CodeBlockKind::Indented => None,
+                };
+                code_block = Some(CodeBlock {
+                    lang,
+                    code: String::new(),
+                });
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some(block) = code_block.take() {
+                    body.code_blocks.push(block);
+                }
+            }
+            Event::Start(Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            }) => {
+                line += content[scanned..range.start].matches('\n').count();
+                scanned = range.start;
+                body.links.push(BodyLink {
+                    dest: dest_url.to_string(),
+                    is_wiki: matches!(link_type, LinkType::WikiLink { .. }),
+                    line,
+                });
+            }
+            Event::Start(Tag::Image { .. }) => image_alt = Some(String::new()),
+            Event::End(TagEnd::Image) => {
+                if let Some(alt) = image_alt.take().filter(|alt| !alt.trim().is_empty()) {
+                    body.image_alt.push(alt.trim().to_string());
+                }
+            }
+            Event::Text(text) => {
+                if let Some(block) = code_block.as_mut() {
+                    block.code.push_str(&text);
+                } else if let Some(alt) = image_alt.as_mut() {
+                    alt.push_str(&text);
+                } else {
+                    if let Some((_, heading_text)) = heading.as_mut() {
+                        heading_text.push_str(&text);
+                    }
+                    body.text.push_str(&text);
+                }
+            }
+            Event::Code(code) => {
+                if let Some(alt) = image_alt.as_mut() {
+                    alt.push_str(&code);
+                    continue;
+                }
+                if let Some((_, heading_text)) = heading.as_mut() {
+                    heading_text.push_str(&code);
+                }
+                body.inline_code.push(code.to_string());
+                body.text.push_str(&code);
+            }
+            Event::SoftBreak | Event::HardBreak => {
+                if let Some(alt) = image_alt.as_mut() {
+                    alt.push(' ');
+                } else if let Some((_, heading_text)) = heading.as_mut() {
+                    // Only the last line of a setext heading is its title, so
+                    // a line of #tags right above it stays out of the heading
+                    heading_text.clear();
+                    body.text.push(' ');
+                } else {
+                    body.text.push('\n');
+                }
+            }
+            Event::End(
+                TagEnd::Paragraph | TagEnd::Item | TagEnd::TableCell | TagEnd::BlockQuote(_),
+            ) => push_separated(&mut body.text, "\n"),
+            _ => {}
+        }
+    }
+    body
+}
+
+/// Find the headings in a markdown body.
+pub fn extract_headings(content: &str) -> Vec<Heading> {
+    parse_body(content).headings
+}
+
+/// The text of the first heading at `level`, used as a title when frontmatter has none.
+pub fn heading_title(content: &str, level: u8) -> Option<String> {
+    extract_headings(content)
+        .into_iter()
+        .find(|heading| heading.level == level)
+        .map(|heading| heading.text)
+}
+
+/// Prefix a body with its title as a heading, unless it already starts with its own H1.
+pub fn with_title_heading(title: &str, content: &str) -> String {
+    if heading_title(content, 1).is_some() {
+        content.to_string()
+    } else {
+        format!("# {title}\n\n{content}")
+    }
+}
+
+/// Read a note to change it, failing when it is not valid UTF-8.
+pub fn get_markdown_str(path: &Path) -> Result<String, InkError> {
+    read_markdown(path, false)
+}
+
+/// Read a note that is never written back, replacing bytes that are not
+/// UTF-8 when `lossy_utf8` is set.
+pub fn get_markdown_str_read_only(path: &Path) -> Result<String, InkError> {
+    read_markdown(path, SETTINGS.lossy_utf8)
+}
+
+fn read_markdown(path: &Path, lossy: bool) -> Result<String, InkError> {
+    let bytes = fs::read(path).map_err(InkError::io(path))?;
+    let message = if SETTINGS.lossy_utf8 {
+        "not valid UTF-8, which lossy_utf8 only allows for commands that do not change notes"
+    } else {
+        "not valid UTF-8, set lossy_utf8 = true to read it anyway"
+    };
+    decode_markdown(bytes, lossy).ok_or_else(|| InkError::parse(path, message))
+}
+
+fn decode_markdown(bytes: Vec<u8>, lossy: bool) -> Option<String> {
+    match String::from_utf8(bytes) {
+        Ok(text) => Some(text),
+        Err(e) if lossy => Some(String::from_utf8_lossy(e.as_bytes()).into_owned()),
+        Err(_) => None,
+    }
+}
+
+/// The byte range of the section under the heading titled `heading`, ignoring
+/// case: from the heading up to the next heading of the same or a higher level.
+pub fn find_section(content: &str, heading: &str) -> Option<Range<usize>> {
+    let mut section: Option<(HeadingLevel, usize)> = None;
+    let mut current: Option<(HeadingLevel, usize, String)> = None;
+    for (event, range) in Parser::new_ext(content, parser_options()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                if let Some((section_level, start)) = section {
+                    if level <= section_level {
+                        return Some(start..range.start);
+                    }
+                }
+                current = Some((level, range.start, String::new()));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, _, title)) = current.as_mut() {
+                    title.push_str(&text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, start, title)) = current.take() {
+                    if section.is_none() && title.trim().eq_ignore_ascii_case(heading.trim()) {
+                        section = Some((level, start));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    section.map(|(_, start)| start..content.len())
+}
+
+/// Render a markdown body to an HTML fragment.
+pub fn markdown(markdown_input: &str) -> String {
+    let parser = Parser::new_ext(markdown_input, parser_options());
 
     // Write to String buffer.
     let mut html_output = String::new();
     html::push_html(&mut html_output, parser);
+    html_output
+}
 
-    Ok(html_output)
-}*/
+/// Parse a frontmatter date, either an RFC 3339 timestamp or a plain `YYYY-MM-DD` date.
+pub fn parse_date(date: &str) -> Option<DateTime<Utc>> {
+    let date = date.trim();
+    DateTime::parse_from_rfc3339(date)
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .map(|date| date.and_utc())
+        })
+}
 
-pub fn frontmatter(markdown_input: &str) -> ParsedMarkdown {
-    let matter = Matter::<YAML>::new();
+fn without_frontmatter(markdown_input: &str) -> ParsedMarkdown {
+    ParsedMarkdown {
+        title: None,
+        tags: None,
+        url: None,
+        created: None,
+        read: None,
+        page: PageMetadata::default(),
+        content: markdown_input.to_string(),
+    }
+}
+
+fn from_frontmatter(data: Option<NoteFrontMatter>, content: String) -> ParsedMarkdown {
+    ParsedMarkdown {
+        title: data.as_ref().and_then(|d| d.title.clone()),
+        tags: data.as_ref().and_then(|d| d.tags.clone()),
+        url: data.as_ref().and_then(|d| d.url.clone()),
+        created: data
+            .as_ref()
+            .and_then(|d| d.created.as_deref())
+            .and_then(parse_date),
+        read: data.as_ref().and_then(|d| d.read),
+        page: data.map(|d| d.page).unwrap_or_default(),
+        content,
+    }
+}
+
+fn parse_delimited<T: Engine>(markdown_input: &str, delimiter: &str) -> ParsedMarkdown {
+    let mut matter = Matter::<T>::new();
+    matter.delimiter = delimiter.to_string();
     matter.parse::<NoteFrontMatter>(markdown_input).map_or_else(
-        |_| ParsedMarkdown {
-            title: None,
-            tags: None,
-            url: None,
-            content: markdown_input.to_string(),
-        },
-        |entity| {
-            let data = entity.data.as_ref();
-            ParsedMarkdown {
-                title: data.and_then(|d| d.title.clone()),
-                tags: data.and_then(|d| d.tags.clone()),
-                url: data.and_then(|d| d.url.clone()),
-                content: entity.content,
-            }
-        },
+        |_| without_frontmatter(markdown_input),
+        |entity| from_frontmatter(entity.data, entity.content),
     )
 }
+
+/// JSON frontmatter is a bare object at the start of the file, as written by Hugo.
+fn parse_json(markdown_input: &str) -> ParsedMarkdown {
+    let mut stream = serde_json::Deserializer::from_str(markdown_input).into_iter();
+    match stream.next() {
+        Some(Ok(data)) => {
+            let rest = &markdown_input[stream.byte_offset()..];
+            let content = rest
+                .strip_prefix("\r\n")
+                .or_else(|| rest.strip_prefix('\n'))
+                .unwrap_or(rest);
+            from_frontmatter(Some(data), content.to_string())
+        }
+        _ => without_frontmatter(markdown_input),
+    }
+}
+
+/// Parse the frontmatter of a note, detecting YAML (`---`), TOML (`+++`) or JSON (`{`)
+/// from the opening delimiter.
+pub fn frontmatter(markdown_input: &str) -> ParsedMarkdown {
+    let markdown_input = markdown_input
+        .strip_prefix('\u{feff}')
+        .unwrap_or(markdown_input);
+    match detect_format(markdown_input) {
+        Some(FrontMatterFormat::Yaml) | None => parse_delimited::<YAML>(markdown_input, "---"),
+        Some(FrontMatterFormat::Toml) => parse_delimited::<TOML>(markdown_input, "+++"),
+        Some(FrontMatterFormat::Json) => parse_json(markdown_input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_parsed(markdown_input: &str) {
+        let parsed = frontmatter(markdown_input);
+        assert_eq!(parsed.title.as_deref(), Some("Hugo Post"));
+        assert_eq!(
+            parsed.tags,
+            Some(vec!["rust".to_string(), "notes".to_string()])
+        );
+        assert_eq!(parsed.url.as_deref(), Some("https://example.com"));
+        assert_eq!(parsed.content.trim(), "# Body");
+    }
+
+    #[test]
+    fn test_frontmatter_yaml() {
+        assert_parsed(
+            "---\ntitle: Hugo Post\ntags:\n  - rust\n  - notes\nurl: https://example.com\n---\n# Body\n",
+        );
+    }
+
+    #[test]
+    fn test_frontmatter_toml() {
+        assert_parsed(
+            "+++\ntitle = \"Hugo Post\"\ntags = [\"rust\", \"notes\"]\nurl = \"https://example.com\"\n+++\n# Body\n",
+        );
+    }
+
+    #[test]
+    fn test_frontmatter_json() {
+        assert_parsed(
+            "{\n  \"title\": \"Hugo Post\",\n  \"tags\": [\"rust\", \"notes\"],\n  \"url\": \"https://example.com\"\n}\n# Body\n",
+        );
+    }
+
+    #[test]
+    fn test_frontmatter_bookmark_metadata() {
+        let parsed = frontmatter(
+            "---\ntitle: Post\nurl: https://example.com\ndescription: \"A post: about things\"\nsite_name: Example\npublished: 2026-10-01\n---\nBody\n",
+        );
+        assert_eq!(parsed.title.as_deref(), Some("Post"));
+        assert_eq!(parsed.page.title, None);
+        assert_eq!(
+            parsed.page.description.as_deref(),
+            Some("A post: about things")
+        );
+        assert_eq!(parsed.page.site_name.as_deref(), Some("Example"));
+        assert_eq!(parsed.page.published.as_deref(), Some("2026-10-01"));
+        assert_eq!(parsed.page.author, None);
+
+        let toml = frontmatter("+++\ntitle = \"Post\"\nauthor = \"Ada\"\n+++\nBody\n");
+        assert_eq!(toml.page.author.as_deref(), Some("Ada"));
+    }
+
+    #[test]
+    fn test_frontmatter_ignores_metadata_of_the_wrong_type() {
+        let parsed = frontmatter(
+            "---\ntitle: Post\ntags: [rust]\nauthor: [Ada, Bob]\npublished: 2024\nsite_name: Example\n---\nBody\n",
+        );
+        assert_eq!(parsed.title.as_deref(), Some("Post"));
+        assert_eq!(parsed.tags, Some(vec!["rust".to_string()]));
+        assert_eq!(parsed.page.author, None);
+        assert_eq!(parsed.page.published, None);
+        assert_eq!(parsed.page.site_name.as_deref(), Some("Example"));
+    }
+
+    #[test]
+    fn test_frontmatter_created() {
+        let parsed = frontmatter("---\ncreated: 2023-11-14T22:13:20Z\n---\n");
+        assert_eq!(parsed.created, DateTime::from_timestamp(1_700_000_000, 0));
+        let parsed = frontmatter("+++\ncreated = \"2023-11-14\"\n+++\n");
+        assert_eq!(parsed.created, DateTime::from_timestamp(1_699_920_000, 0));
+        assert_eq!(frontmatter("---\ncreated: someday\n---\n").created, None);
+    }
+
+    #[test]
+    fn test_frontmatter_read() {
+        assert_eq!(frontmatter("---\nread: false\n---\n").read, Some(false));
+        assert_eq!(frontmatter("+++\nread = true\n+++\n").read, Some(true));
+        assert_eq!(frontmatter("{\"read\": false}\n").read, Some(false));
+        assert_eq!(frontmatter("---\ntitle: Unqueued\n---\n").read, None);
+        let dated = frontmatter("---\ntitle: Dated\nread: 2024-05-01\n---\n");
+        assert_eq!(dated.read, None);
+        assert_eq!(dated.title.as_deref(), Some("Dated"));
+    }
+
+    #[test]
+    fn test_decode_markdown() {
+        let latin1 = b"---\ntitle: Caf\xe9\n---\n".to_vec();
+        assert_eq!(decode_markdown(latin1.clone(), false), None);
+        assert_eq!(
+            decode_markdown(latin1, true).as_deref(),
+            Some("---\ntitle: Caf\u{fffd}\n---\n")
+        );
+        assert_eq!(
+            decode_markdown(b"# Caf\xc3\xa9".to_vec(), false).as_deref(),
+            Some("# Café")
+        );
+    }
+
+    #[test]
+    fn test_frontmatter_with_bom() {
+        assert_parsed("\u{feff}+++\ntitle = \"Hugo Post\"\ntags = [\"rust\", \"notes\"]\nurl = \"https://example.com\"\n+++\n# Body\n");
+    }
+
+    #[test]
+    fn test_extract_headings() {
+        let content = "# Title #\n\nText\n\n## Section ##\n```\n# not a heading\n```\n#hashtag\nSetext\n---\n    # indented code\n###\n";
+        let headings: Vec<(u8, String)> = extract_headings(content)
+            .into_iter()
+            .map(|heading| (heading.level, heading.text))
+            .collect();
+        assert_eq!(
+            headings,
+            vec![
+                (1, "Title".to_string()),
+                (2, "Section".to_string()),
+                (2, "Setext".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_body_splits_fields() {
+        let content = concat!(
+            "# Setup `ink`\n\n",
+            "Read the [docs](https://example.com/docs) and [[Other Note|the other note]].\n\n",
+            "![A diagram of the index](diagram.png)\n\n",
+            "```rust\nfn main() {}\n```\n\n",
+            "<div>html</div>\n\n",
+            "- [ ] Call `cargo build`\n",
+        );
+        let body = parse_body(content);
+        assert_eq!(
+            body.headings,
+            vec![Heading {
+                level: 1,
+                text: "Setup ink".to_string()
+            }]
+        );
+        assert_eq!(
+            body.code_blocks,
+            vec![CodeBlock {
+                lang: Some("rust".to_string()),
+                code: "fn main() {}\n".to_string()
+            }]
+        );
+        assert_eq!(body.inline_code, vec!["ink", "cargo build"]);
+        let links: Vec<(&str, bool, usize)> = body
+            .links
+            .iter()
+            .map(|link| (link.dest.as_str(), link.is_wiki, link.line))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("https://example.com/docs", false, 3),
+                ("Other Note", true, 3)
+            ]
+        );
+        assert_eq!(body.image_alt, vec!["A diagram of the index"]);
+
+        assert!(body.text.contains("Read the docs and the other note."));
+        assert!(body.text.contains("Call cargo build"));
+        for noise in ["https://", "fn main", "<div>", "diagram", "[ ]"] {
+            assert!(!body.text.contains(noise), "{noise} in {:?}", body.text);
+        }
+    }
+
+    #[test]
+    fn test_heading_title() {
+        let content = "Intro\n\n## Sub\n\n# Real Title\n";
+        assert_eq!(heading_title(content, 1).as_deref(), Some("Real Title"));
+        assert_eq!(heading_title(content, 2).as_deref(), Some("Sub"));
+        assert_eq!(heading_title(content, 3), None);
+    }
+
+    #[test]
+    fn test_find_section() {
+        let content =
+            "# Note\n\n## Log\n\nEntry\n\n### Detail\n\n```\n## Not a heading\n```\n\n## Next\n";
+        let section = find_section(content, "log").unwrap();
+        assert_eq!(
+            &content[section],
+            "## Log\n\nEntry\n\n### Detail\n\n```\n## Not a heading\n```\n\n"
+        );
+        let last = find_section(content, "Next").unwrap();
+        assert_eq!(&content[last], "## Next\n");
+        assert_eq!(find_section(content, "Not a heading"), None);
+    }
+
+    #[test]
+    fn test_frontmatter_missing() {
+        let parsed = frontmatter("# Just a body\n");
+        assert_eq!(parsed.title, None);
+        assert_eq!(parsed.content.trim(), "# Just a body");
+    }
+}
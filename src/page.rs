@@ -0,0 +1,194 @@
+use crate::http::{fetch_page, FetchError};
+use crate::markdown::ignore_wrong_type;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+
+/// What a bookmarked page says about itself, from Open Graph, Twitter card and
+/// standard HTML metadata.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageMetadata {
+    /// Becomes the note title rather than a frontmatter field
+    #[serde(skip)]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "ignore_wrong_type")]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "ignore_wrong_type")]
+    pub site_name: Option<String>,
+    #[serde(default, deserialize_with = "ignore_wrong_type")]
+    pub author: Option<String>,
+    /// As published by the page, usually an ISO 8601 date or timestamp
+    #[serde(default, deserialize_with = "ignore_wrong_type")]
+    pub published: Option<String>,
+    #[serde(default, deserialize_with = "ignore_wrong_type")]
+    pub canonical_url: Option<String>,
+    #[serde(default, deserialize_with = "ignore_wrong_type")]
+    pub favicon_url: Option<String>,
+}
+
+impl PageMetadata {
+    /// The fields stored in frontmatter, by name.
+    pub fn fields(&self) -> [(&'static str, &Option<String>); 6] {
+        [
+            ("description", &self.description),
+            ("site_name", &self.site_name),
+            ("author", &self.author),
+            ("published", &self.published),
+            ("canonical_url", &self.canonical_url),
+            ("favicon_url", &self.favicon_url),
+        ]
+    }
+}
+
+/// Trim and collapse runs of whitespace, treating blank text as missing.
+fn clean_text(text: &str) -> Option<String> {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+fn select_first<'a>(document: &'a Html, selector: &str) -> Option<ElementRef<'a>> {
+    let selector = Selector::parse(selector).expect("Invalid metadata selector");
+    document.select(&selector).next()
+}
+
+/// The first non-empty attribute value among elements matching `selectors`, in order.
+fn first_attribute(document: &Html, selectors: &[&str], attribute: &str) -> Option<String> {
+    selectors.iter().find_map(|selector| {
+        select_first(document, selector)
+            .and_then(|element| element.value().attr(attribute))
+            .and_then(clean_text)
+    })
+}
+
+fn meta_content(document: &Html, selectors: &[&str]) -> Option<String> {
+    first_attribute(document, selectors, "content")
+}
+
+/// Extract metadata from a page, resolving relative URLs against `page_url`.
+pub fn extract_metadata(html: &str, page_url: &Url) -> PageMetadata {
+    let document = Html::parse_document(html);
+    let absolute = |href: String| page_url.join(&href).ok().map(String::from);
+
+    let title = meta_content(
+        &document,
+        &[
+            r#"meta[property="og:title"]"#,
+            r#"meta[name="twitter:title"]"#,
+        ],
+    )
+    .or_else(|| {
+        select_first(&document, "title")
+            .and_then(|element| clean_text(&element.text().collect::<String>()))
+    });
+
+    PageMetadata {
+        title,
+        description: meta_content(
+            &document,
+            &[
+                r#"meta[property="og:description"]"#,
+                r#"meta[name="twitter:description"]"#,
+                r#"meta[name="description"]"#,
+            ],
+        ),
+        site_name: meta_content(
+            &document,
+            &[
+                r#"meta[property="og:site_name"]"#,
+                r#"meta[name="application-name"]"#,
+            ],
+        ),
+        author: meta_content(
+            &document,
+            &[
+                r#"meta[name="author"]"#,
+                r#"meta[property="article:author"]"#,
+                r#"meta[name="twitter:creator"]"#,
+            ],
+        ),
+        published: meta_content(
+            &document,
+            &[
+                r#"meta[property="article:published_time"]"#,
+                r#"meta[itemprop="datePublished"]"#,
+                r#"meta[name="date"]"#,
+            ],
+        ),
+        canonical_url: first_attribute(&document, &[r#"link[rel="canonical"]"#], "href")
+            .or_else(|| meta_content(&document, &[r#"meta[property="og:url"]"#]))
+            .and_then(absolute),
+        favicon_url: first_attribute(
+            &document,
+            &[r#"link[rel~="icon"]"#, r#"link[rel="apple-touch-icon"]"#],
+            "href",
+        )
+        .and_then(absolute),
+    }
+}
+
+/// Fetch a page and extract its metadata.
+pub fn fetch_page_metadata(url: &str) -> Result<PageMetadata, FetchError> {
+    let page = fetch_page(url)?;
+    Ok(extract_metadata(&page.body, &page.url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page_url() -> Url {
+        Url::parse("https://example.com/posts/hello").unwrap()
+    }
+
+    #[test]
+    fn test_extract_metadata() {
+        let html = r#"<!DOCTYPE html>
+<html><head>
+<title>
+    Hello &amp; welcome | Example
+</title>
+<meta property="og:title" content="Hello &amp; welcome">
+<meta property="og:description" content="  A post
+    about things.  ">
+<meta name="description" content="Fallback description">
+<meta property="og:site_name" content="Example Blog">
+<meta name="author" content="Ada Lovelace">
+<meta property="article:published_time" content="2026-10-01T09:00:00Z">
+<link rel="canonical" href="/posts/hello-world">
+<link rel="shortcut icon" href="/static/favicon.png">
+</head><body></body></html>"#;
+        assert_eq!(
+            extract_metadata(html, &page_url()),
+            PageMetadata {
+                title: Some("Hello & welcome".to_string()),
+                description: Some("A post about things.".to_string()),
+                site_name: Some("Example Blog".to_string()),
+                author: Some("Ada Lovelace".to_string()),
+                published: Some("2026-10-01T09:00:00Z".to_string()),
+                canonical_url: Some("https://example.com/posts/hello-world".to_string()),
+                favicon_url: Some("https://example.com/static/favicon.png".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_extract_metadata_fallbacks() {
+        let html = r#"<html><head>
+<title> Plain   &lt;title&gt; </title>
+<meta name="twitter:description" content="From the card">
+<meta name="twitter:creator" content="@someone">
+<meta property="og:url" content="https://example.com/canonical">
+<meta property="og:title" content="   ">
+</head></html>"#;
+        let metadata = extract_metadata(html, &page_url());
+        assert_eq!(metadata.title, Some("Plain <title>".to_string()));
+        assert_eq!(metadata.description, Some("From the card".to_string()));
+        assert_eq!(metadata.author, Some("@someone".to_string()));
+        assert_eq!(
+            metadata.canonical_url,
+            Some("https://example.com/canonical".to_string())
+        );
+        assert_eq!(metadata.site_name, None);
+        assert_eq!(metadata.favicon_url, None);
+    }
+}
@@ -18,6 +18,8 @@ pub struct ParsedQuery {
     pub query: String,
     pub tags: Vec<String>,
     pub url: Option<String>,
+    /// `read:true` or `read:false`, to filter bookmarks by read state
+    pub read: Option<bool>,
 }
 
 impl ParsedQuery {
@@ -25,12 +27,15 @@ impl ParsedQuery {
         let mut tags = Vec::new();
         let mut query_parts = Vec::new();
         let mut url: Option<String> = None;
+        let mut read: Option<bool> = None;
 
         for part in query.split_whitespace() {
             if part.starts_with('#') {
                 tags.push(part.trim_start_matches('#').to_string());
             } else if find_url(part).is_some() {
                 url = Some(part.to_string());
+            } else if let Some(value) = part.strip_prefix("read:").and_then(|v| v.parse().ok()) {
+                read = Some(value);
             } else {
                 query_parts.push(part.to_string());
             }
@@ -42,6 +47,7 @@ impl ParsedQuery {
             query: prompt,
             tags,
             url,
+            read,
         }
     }
 
@@ -0,0 +1,250 @@
+use crate::error::InkError;
+use crate::markdown::get_markdown_str_read_only;
+use crate::note::Note;
+use crate::search::{indexed_notes, typeahead_matches};
+use crate::settings::SETTINGS;
+use crate::walk::{has_extension, walk_files};
+use std::fmt;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// Fuzzy matches scoring at least this fraction of the best match are considered ambiguous.
+const FUZZY_CANDIDATE_RATIO: f32 = 0.75;
+const MAX_CANDIDATES: usize = 10;
+
+#[derive(Debug)]
+pub enum ResolveError {
+    NotFound(String),
+    Ambiguous(String, Vec<PathBuf>),
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolveError::NotFound(query) => write!(f, "No note found for {query}"),
+            ResolveError::Ambiguous(query, candidates) => {
+                write!(f, "{query} matches several notes:")?;
+                for candidate in candidates {
+                    write!(f, "\n  {}", candidate.display())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+struct Candidate {
+    path: PathBuf,
+    id: String,
+    title: String,
+}
+
+fn existing_file(path: &Path) -> Option<PathBuf> {
+    path.is_file().then(|| path.to_path_buf())
+}
+
+/// A path, absolute or relative to the notes folder or the current directory.
+fn resolve_path(query: &str) -> Option<PathBuf> {
+    let candidate = Path::new(query);
+    if candidate.is_absolute() {
+        return existing_file(candidate);
+    }
+    let in_notes = SETTINGS.get_notes_path().join(candidate);
+    existing_file(&in_notes)
+        .or_else(|| existing_file(&in_notes.with_extension("md")))
+        .or_else(|| {
+            std::env::current_dir()
+                .ok()
+                .and_then(|cwd| existing_file(&cwd.join(candidate)))
+        })
+}
+
+/// The indexed notes whose files still exist, or none when there is no index.
+fn index_candidates() -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = indexed_notes()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|note| Candidate {
+            path: note.get_file_path(),
+            id: note.id.to_lowercase(),
+            title: note.title.to_lowercase(),
+        })
+        .filter(|candidate| candidate.path.is_file())
+        .collect();
+    candidates.sort_by(|a, b| a.path.cmp(&b.path));
+    candidates
+}
+
+fn vault_candidates() -> Result<Vec<Candidate>, InkError> {
+    let mut candidates = Vec::new();
+    walk_files(&SETTINGS.get_notes_path(), true, has_extension, |path| {
+        let note = Note::from_markdown(path, &get_markdown_str_read_only(path)?);
+        candidates.push(Candidate {
+            path: path.to_path_buf(),
+            id: note.id.to_lowercase(),
+            title: note.title.to_lowercase(),
+        });
+        Ok(())
+    })?;
+    candidates.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(candidates)
+}
+
+/// `None` when nothing matched, so the next, looser, strategy can be tried.
+fn unique_match(query: &str, mut paths: Vec<PathBuf>) -> Option<Result<PathBuf, ResolveError>> {
+    match paths.len() {
+        0 => None,
+        1 => paths.pop().map(Ok),
+        _ => {
+            paths.truncate(MAX_CANDIDATES);
+            Some(Err(ResolveError::Ambiguous(query.to_string(), paths)))
+        }
+    }
+}
+
+fn bigrams(text: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+}
+
+/// Whether `title` contains at least half of the letter pairs of `query`.
+fn covers_query(title: &str, query: &str) -> bool {
+    let query_bigrams = bigrams(query);
+    let title_bigrams = bigrams(title);
+    let found = query_bigrams
+        .iter()
+        .filter(|bigram| title_bigrams.contains(bigram))
+        .count();
+    !query_bigrams.is_empty() && found * 2 >= query_bigrams.len()
+}
+
+type Strategy<'a> = &'a dyn Fn(&Candidate) -> bool;
+
+/// The match of the first strategy that matches any candidate.
+fn first_match(
+    query: &str,
+    candidates: &[Candidate],
+    strategies: &[Strategy],
+) -> Option<Result<PathBuf, ResolveError>> {
+    strategies.iter().find_map(|is_match| {
+        let paths = candidates
+            .iter()
+            .filter(|candidate| is_match(candidate))
+            .map(|candidate| candidate.path.clone())
+            .collect();
+        unique_match(query, paths)
+    })
+}
+
+fn fuzzy_match(query: &str) -> Option<Result<PathBuf, ResolveError>> {
+    // No index means no fuzzy matching, rather than an error
+    let mut matches = typeahead_matches(query, MAX_CANDIDATES).ok()?;
+    // Titles sharing a couple of common letter pairs are not what was meant
+    matches.retain(|(_, note)| covers_query(&note.title, query));
+    let best_score = matches.first()?.0;
+    let paths = matches
+        .into_iter()
+        .filter(|(score, _)| *score >= best_score * FUZZY_CANDIDATE_RATIO)
+        .map(|(_, note)| note.get_file_path())
+        .collect();
+    unique_match(query, paths)
+}
+
+/// Find a note without prompting.
+///
+/// Tries, in order: a path (absolute, relative to the notes folder or to the
+/// current directory, with or without the `.md` extension), an id, an exact
+/// title, an id or title prefix, then a fuzzy match on indexed titles. The
+/// first strategy with any match wins, and fails if it matched several notes.
+///
+/// Ids and exact titles are looked up in the index first. The notes folder is
+/// only walked when that finds nothing, to catch notes the index is missing.
+pub fn find_note(query: &str) -> Result<PathBuf, InkError> {
+    if let Some(path) = resolve_path(query) {
+        return Ok(path);
+    }
+
+    let lowercase_query = query.to_lowercase();
+    let exact: [Strategy; 2] = [&|candidate| candidate.id == lowercase_query, &|candidate| {
+        candidate.title == lowercase_query
+    }];
+    if let Some(result) = first_match(query, &index_candidates(), &exact) {
+        return Ok(result?);
+    }
+
+    let prefix: Strategy = &|candidate| {
+        candidate.id.starts_with(&lowercase_query) || candidate.title.starts_with(&lowercase_query)
+    };
+    if let Some(result) = first_match(query, &vault_candidates()?, &[exact[0], exact[1], prefix]) {
+        return Ok(result?);
+    }
+
+    Ok(fuzzy_match(query).unwrap_or_else(|| Err(ResolveError::NotFound(query.to_string())))?)
+}
+
+/// Ask which note was meant, when attached to a terminal.
+fn choose_candidate(query: &str, candidates: &[PathBuf]) -> Option<PathBuf> {
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        return None;
+    }
+    let mut stderr = std::io::stderr();
+    let _ = writeln!(stderr, "{query} matches several notes:");
+    for (i, candidate) in candidates.iter().enumerate() {
+        let _ = writeln!(stderr, "  {}) {}", i + 1, candidate.display());
+    }
+    let _ = write!(stderr, "Choose a note [1-{}]: ", candidates.len());
+    let _ = stderr.flush();
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer).ok()?;
+    let choice = answer.trim().parse::<usize>().ok()?;
+    candidates.get(choice.checked_sub(1)?).cloned()
+}
+
+/// Find the markdown file a note argument refers to, prompting when it is ambiguous.
+pub fn resolve_note(query: &str) -> Result<PathBuf, InkError> {
+    match find_note(query) {
+        Err(InkError::Resolve(ResolveError::Ambiguous(query, candidates))) => {
+            choose_candidate(&query, &candidates).ok_or(InkError::Resolve(ResolveError::Ambiguous(
+                query, candidates,
+            )))
+        }
+        result => result,
+    }
+}
+
+/// Print the path of the note a query resolves to.
+pub fn print_resolved(query: &str) -> Result<(), InkError> {
+    println!("{}", resolve_note(query)?.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unique_match() {
+        assert!(unique_match("q", Vec::new()).is_none());
+        assert_eq!(
+            unique_match("q", vec![PathBuf::from("a.md")])
+                .unwrap()
+                .unwrap(),
+            PathBuf::from("a.md")
+        );
+        let ambiguous = unique_match("q", vec![PathBuf::from("a.md"), PathBuf::from("b.md")])
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(
+            ambiguous.to_string(),
+            "q matches several notes:\n  a.md\n  b.md"
+        );
+    }
+
+    #[test]
+    fn test_covers_query() {
+        assert!(covers_query("Rust Ownership", "Ownrship"));
+        assert!(!covers_query("Groceries", "quinces"));
+        assert!(!covers_query("Anything", "x"));
+    }
+}
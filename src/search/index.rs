@@ -1,9 +1,13 @@
+use crate::error::InkError;
+use crate::markdown::get_markdown_str_read_only;
 use crate::note::Note;
-use crate::search::index_updater::update_index_metadata;
+use crate::search::index_updater::{clear_index_metadata, update_index_metadata};
 use crate::settings::SETTINGS;
+use crate::tasks::note_tasks;
 use crate::utils::ensure_directory_exists;
 use crate::walk::{has_extension, walk_files};
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use tantivy::tokenizer::NgramTokenizer;
 use tantivy::{
     schema::{
@@ -13,36 +17,71 @@ use tantivy::{
     Index, IndexWriter, TantivyError,
 };
 
-fn add_document(note: &Note, index_writer: &IndexWriter, schema: &Schema) {
+/// Add a note and its tasks, replacing any documents previously indexed for its path.
+fn add_document(note: &Note, raw_markdown: &str, index_writer: &IndexWriter, schema: &Schema) {
     let path_field: Field = schema.get_field("path").unwrap();
     let path = note.get_file_path();
-    let path_str = path
-        .to_str()
-        .expect("Valid path required to index document");
+    let path_str = path.to_string_lossy();
 
     // Create a term to identify the document to delete
-    let term = Term::from_field_text(path_field, path_str);
+    let term = Term::from_field_text(path_field, &path_str);
 
     index_writer.delete_term(term);
     // Delete any existing document with the same path
 
     let _ = index_writer.add_document(note.to_tantivy_document(schema));
+
+    let is_hidden = note.is_hidden();
+    for task in note_tasks(raw_markdown, &path) {
+        let _ = index_writer.add_document(task.to_tantivy_document(schema, is_hidden));
+    }
+}
+
+fn index_file(
+    markdown_path: &Path,
+    schema: &Schema,
+    index_writer: &IndexWriter,
+) -> Result<(), InkError> {
+    let raw_markdown = get_markdown_str_read_only(markdown_path)?;
+    let note = Note::from_markdown(markdown_path, &raw_markdown);
+    add_document(&note, &raw_markdown, index_writer, schema);
+    Ok(())
 }
 
-fn index_file(markdown_path: &str, schema: &Schema, index_writer: &IndexWriter) {
-    let note = Note::from_markdown_file(markdown_path);
-    add_document(&note, index_writer, schema);
+/// Delete the files of an existing index, leaving anything else in the cache folder alone.
+fn remove_index_files(index_path: &Path) -> std::io::Result<()> {
+    let managed_path = index_path.join(".managed.json");
+    let managed: Vec<String> = fs::read_to_string(&managed_path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    for file_name in managed.iter().map(String::as_str).chain(["meta.json"]) {
+        let path = index_path.join(file_name);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
+    if managed_path.exists() {
+        fs::remove_file(managed_path)?;
+    }
+    Ok(())
 }
 
 // Handling Index
 fn open_or_create_index(index_path: &PathBuf, schema: &Schema) -> Result<Index, TantivyError> {
     ensure_directory_exists(index_path)?;
     if let Ok(index) = Index::open_in_dir(index_path) {
-        Ok(index)
-    } else {
-        println!("Creating index in {}", &index_path.to_str().unwrap());
-        Index::create_in_dir(index_path, schema.clone())
+        if index.schema() == *schema {
+            return Ok(index);
+        }
+        // Indexes written by an older version of ink are rebuilt from scratch
+        eprintln!("Index schema changed, recreating index");
+        drop(index);
+        remove_index_files(index_path)?;
+        clear_index_metadata()?;
     }
+    eprintln!("Creating index in {}", index_path.display());
+    Index::create_in_dir(index_path, schema.clone())
 }
 
 fn get_index(schema: &Schema) -> Result<Index, TantivyError> {
@@ -53,7 +92,7 @@ fn get_index(schema: &Schema) -> Result<Index, TantivyError> {
     Ok(index)
 }
 
-fn get_schema() -> Schema {
+pub fn get_schema() -> Schema {
     let mut schema_builder = Schema::builder();
     let typeahead_options = TextOptions::default().set_stored().set_indexing_options(
         TextFieldIndexing::default()
@@ -66,6 +105,12 @@ fn get_schema() -> Schema {
             .set_index_option(IndexRecordOption::Basic),
     );
     let stored_text_options = text_options.clone().set_stored();
+    // Code and URLs are split on punctuation rather than stemmed, with positions for phrases
+    let code_options = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer("default")
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    );
     schema_builder.add_date_field("sort_created", FAST);
     schema_builder.add_date_field("sort_modified", FAST);
     schema_builder.add_date_field("created", INDEXED | STORED);
@@ -75,16 +120,29 @@ fn get_schema() -> Schema {
     schema_builder.add_text_field("sort_title", FAST);
 
     schema_builder.add_bool_field("is_hidden", INDEXED | FAST | STORED);
+    schema_builder.add_bool_field("read", INDEXED | STORED);
 
     schema_builder.add_text_field("title", stored_text_options);
+    schema_builder.add_text_field("headings", text_options.clone());
+    schema_builder.add_text_field("image_alt", text_options.clone());
     schema_builder.add_text_field("body", text_options);
+    schema_builder.add_text_field("code", code_options.clone());
+    schema_builder.add_text_field("lang", STRING);
+    schema_builder.add_text_field("links", code_options);
     schema_builder.add_text_field("path", STRING | STORED);
     schema_builder.add_facet_field("tag", INDEXED | STORED);
+
+    // Notes and the tasks found in them share the index, told apart by kind
+    schema_builder.add_text_field("kind", STRING | STORED);
+    schema_builder.add_text_field("task_id", STRING | STORED);
+    schema_builder.add_u64_field("line", STORED);
+    schema_builder.add_bool_field("done", INDEXED | STORED);
+    schema_builder.add_date_field("due", INDEXED | STORED | FAST);
     // Build the schema
     schema_builder.build()
 }
 
-pub fn create_index_and_add_documents() -> tantivy::Result<()> {
+pub fn create_index_and_add_documents() -> Result<(), InkError> {
     let schema = get_schema();
     let index = get_index(&schema)?;
 
@@ -92,14 +150,43 @@ pub fn create_index_and_add_documents() -> tantivy::Result<()> {
     let mut index_writer = index.writer(50_000_000)?;
 
     walk_files(&SETTINGS.get_notes_path(), true, has_extension, |path| {
-        index_file(path, &schema, &index_writer);
-    });
+        index_file(path, &schema, &index_writer)
+    })?;
 
     index_writer.commit()?;
 
     let reader = index.reader()?;
     let searcher = reader.searcher();
     println!("Indexed {} documents", searcher.num_docs());
-    update_index_metadata().expect("Error writing index metadata file");
+    update_index_metadata()?;
+    Ok(())
+}
+
+/// Re-index a handful of files without walking the whole notes folder.
+pub fn reindex_files(paths: &[PathBuf]) -> Result<(), InkError> {
+    let schema = get_schema();
+    let index = get_index(&schema)?;
+    let mut index_writer: IndexWriter = index.writer(50_000_000)?;
+
+    for path in paths {
+        index_file(path, &schema, &index_writer)?;
+    }
+
+    index_writer.commit()?;
+    Ok(())
+}
+
+/// Drop the documents of deleted files from the index.
+pub fn remove_files(paths: &[PathBuf]) -> Result<(), InkError> {
+    let schema = get_schema();
+    let index = get_index(&schema)?;
+    let mut index_writer: IndexWriter = index.writer(50_000_000)?;
+    let path_field = schema.get_field("path").unwrap();
+
+    for path in paths {
+        index_writer.delete_term(Term::from_field_text(path_field, &path.to_string_lossy()));
+    }
+
+    index_writer.commit()?;
     Ok(())
 }
@@ -1,3 +1,4 @@
+use crate::error::InkError;
 use crate::settings::SETTINGS;
 use std::fs;
 use std::io::Read;
@@ -9,12 +10,22 @@ fn get_metadata_path() -> PathBuf {
     SETTINGS.get_cache_path().join("index_metadata.txt")
 }
 
-pub fn update_index_metadata() -> std::io::Result<()> {
+pub fn update_index_metadata() -> Result<(), InkError> {
     let now = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap()
         .as_secs();
-    fs::write(get_metadata_path(), now.to_string())
+    let metadata_path = get_metadata_path();
+    fs::write(&metadata_path, now.to_string()).map_err(InkError::io(&metadata_path))
+}
+
+/// Forget when the index was last updated, so the next search rebuilds it.
+pub fn clear_index_metadata() -> std::io::Result<()> {
+    let metadata_path = get_metadata_path();
+    if metadata_path.exists() {
+        fs::remove_file(metadata_path)?;
+    }
+    Ok(())
 }
 
 pub fn index_needs_update() -> std::io::Result<bool> {
@@ -35,7 +46,14 @@ pub fn index_needs_update() -> std::io::Result<bool> {
 }
 
 pub fn spawn_index_update() {
-    match Command::new(std::env::current_exe().unwrap())
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("Failed to spawn indexing process: {e}");
+            return;
+        }
+    };
+    match Command::new(exe)
         .arg("index")
         .stdout(Stdio::null())
         .stderr(Stdio::null())
@@ -2,5 +2,7 @@ mod index;
 mod index_updater;
 mod query;
 
-pub use self::index::create_index_and_add_documents;
-pub use self::query::search_index;
+#[cfg(test)]
+pub use self::index::get_schema;
+pub use self::index::{create_index_and_add_documents, reindex_files, remove_files};
+pub use self::query::{find_notes, find_tasks, indexed_notes, search_index, typeahead_matches};
@@ -3,13 +3,15 @@ use crate::note::Note;
 use crate::prompt::ParsedQuery;
 use crate::search::index_updater::{index_needs_update, spawn_index_update};
 use crate::settings::SETTINGS;
+use crate::tasks::{date_to_tantivy, Task, TaskFilter};
+use std::ops::Bound;
 use tantivy::tokenizer::NgramTokenizer;
 use tantivy::DateTime as tantivy_DateTime;
 use tantivy::{
     collector::TopDocs,
     index::Order,
-    query::{BooleanQuery, BoostQuery, Occur, Query, QueryParser, TermQuery},
-    schema::{Facet, IndexRecordOption, TantivyDocument, Term},
+    query::{AllQuery, BooleanQuery, BoostQuery, Occur, Query, QueryParser, RangeQuery, TermQuery},
+    schema::{Facet, IndexRecordOption, Schema, TantivyDocument, Term},
     DocAddress, Index, Searcher,
 };
 
@@ -20,14 +22,61 @@ pub fn search_index(
     limit: usize,
     include_ignored: bool,
 ) -> tantivy::Result<()> {
+    let top_notes = find_notes(query, sort, limit, include_ignored)?;
+    if is_json {
+        println!("{}", serde_json::to_string(&top_notes).unwrap());
+    } else {
+        for note in &top_notes {
+            println!("{}\t{}", note.title, note.get_file_path().display());
+        }
+    }
+
+    Ok(())
+}
+
+fn open_index() -> tantivy::Result<Index> {
     if index_needs_update()? {
         spawn_index_update();
     }
+    open_existing_index()
+}
+
+fn open_existing_index() -> tantivy::Result<Index> {
     // Open the index
     let index_path = &SETTINGS.get_cache_path();
     let index = Index::open_in_dir(index_path)?;
     let ngram_tokenizer = NgramTokenizer::new(2, 7, false).unwrap();
     index.tokenizers().register("ngram", ngram_tokenizer);
+    Ok(index)
+}
+
+fn kind_query(schema: &Schema, kind: &str) -> Option<Box<dyn Query>> {
+    let kind_field = schema.get_field("kind").ok()?;
+    Some(Box::new(TermQuery::new(
+        Term::from_field_text(kind_field, kind),
+        IndexRecordOption::Basic,
+    )))
+}
+
+fn tag_query(schema: &Schema, tag: &str) -> Box<dyn Query> {
+    let facet = Facet::from(&format!("/tag/{tag}"));
+    let facet_term = Term::from_facet(schema.get_field("tag").unwrap(), &facet);
+    Box::new(TermQuery::new(facet_term, IndexRecordOption::Basic))
+}
+
+fn visible_query(schema: &Schema) -> Box<dyn Query> {
+    let is_hidden_term = Term::from_field_bool(schema.get_field("is_hidden").unwrap(), false);
+    Box::new(TermQuery::new(is_hidden_term, IndexRecordOption::Basic))
+}
+
+/// Run a search query against the index and return the matching notes.
+pub fn find_notes(
+    query: &str,
+    sort: Option<SortChoice>,
+    limit: usize,
+    include_ignored: bool,
+) -> tantivy::Result<Vec<Note>> {
+    let index = open_index()?;
 
     let parsed_query = ParsedQuery::from_query(query);
 
@@ -36,8 +85,10 @@ pub fn search_index(
 
     let title_weight = 2.0;
     let lookahead_weight = 1.5;
+    let headings_weight = 1.5;
 
-    if !parsed_query.query.is_empty() {
+    let has_text_query = !parsed_query.query.is_empty();
+    if has_text_query {
         let query_str = &parsed_query.query.to_lowercase();
 
         // typeahead
@@ -56,6 +107,14 @@ pub fn search_index(
         let boosted_title_query = BoostQuery::new(Box::new(title_query), title_weight);
         queries.push((Occur::Should, Box::new(boosted_title_query)));
 
+        // headings, missing from indexes built before they were added
+        if let Ok(headings_field) = schema.get_field("headings") {
+            let headings_query =
+                QueryParser::for_index(&index, vec![headings_field]).parse_query(query_str)?;
+            let boosted_headings_query = BoostQuery::new(headings_query, headings_weight);
+            queries.push((Occur::Should, Box::new(boosted_headings_query)));
+        }
+
         // body
         let body_query = QueryParser::for_index(&index, vec![schema.get_field("body").unwrap()])
             .parse_query(query_str)?;
@@ -63,16 +122,25 @@ pub fn search_index(
     }
 
     if !include_ignored {
-        let is_hidden_term = Term::from_field_bool(schema.get_field("is_hidden").unwrap(), false);
-        let is_hidden_query = TermQuery::new(is_hidden_term, IndexRecordOption::Basic);
-        queries.push((Occur::Must, Box::new(is_hidden_query)));
+        queries.push((Occur::Must, visible_query(&schema)));
     }
 
     for tag in parsed_query.tags {
-        let facet = Facet::from(&format!("/tag/{tag}"));
-        let facet_term = Term::from_facet(schema.get_field("tag").unwrap(), &facet);
-        let facet_query = TermQuery::new(facet_term, IndexRecordOption::Basic);
-        queries.push((Occur::Must, Box::new(facet_query)));
+        queries.push((Occur::Must, tag_query(&schema, &tag)));
+    }
+
+    // read state, missing from indexes built before the read-later queue
+    if let (Some(read), Ok(read_field)) = (parsed_query.read, schema.get_field("read")) {
+        let read_term = Term::from_field_bool(read_field, read);
+        queries.push((
+            Occur::Must,
+            Box::new(TermQuery::new(read_term, IndexRecordOption::Basic)),
+        ));
+    }
+
+    // Task documents are only returned by find_tasks
+    if let Some(note_kind_query) = kind_query(&schema, "note") {
+        queries.push((Occur::Must, note_kind_query));
     }
 
     let combined_query = BooleanQuery::new(queries);
@@ -80,8 +148,15 @@ pub fn search_index(
     // Create a searcher
     //
     let searcher = index.reader()?.searcher();
+    // Never ask the collector for more documents than the index holds
+    let limit = limit.min(
+        usize::try_from(searcher.num_docs())
+            .unwrap_or(usize::MAX)
+            .max(1),
+    );
     // Define minimum score threshold
-    let min_score_threshold = 0.5;
+    // Only free text is scored for relevance, filters alone always match
+    let min_score_threshold = if has_text_query { 0.5 } else { 0.0 };
 
     let top_docs = match sort {
         Some(SortChoice::DescLastModified) => get_datetime_top_docs(
@@ -113,15 +188,117 @@ pub fn search_index(
             Note::from_tantivy_document(&doc, &schema)
         })
         .collect();
-    if is_json {
-        println!("{}", serde_json::to_string(&top_notes).unwrap());
-    } else {
-        for note in &top_notes {
-            println!("{}\t{}", note.title, note.get_file_path().to_str().unwrap());
-        }
+    Ok(top_notes)
+}
+
+/// Return the indexed tasks matching `filter`, earliest due date first.
+pub fn find_tasks(filter: &TaskFilter) -> tantivy::Result<Vec<Task>> {
+    let index = open_index()?;
+    let schema = index.schema();
+    let Some(task_kind_query) = kind_query(&schema, "task") else {
+        // Indexes built before tasks were extracted have none
+        return Ok(Vec::new());
+    };
+
+    let mut queries: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, task_kind_query)];
+    if !filter.include_ignored {
+        queries.push((Occur::Must, visible_query(&schema)));
+    }
+    if let Some(done) = filter.done {
+        let done_term = Term::from_field_bool(schema.get_field("done").unwrap(), done);
+        let done_query = TermQuery::new(done_term, IndexRecordOption::Basic);
+        queries.push((Occur::Must, Box::new(done_query)));
+    }
+    if let Some(due_before) = filter.due_before {
+        let due_term = Term::from_field_date_for_search(
+            schema.get_field("due").unwrap(),
+            date_to_tantivy(due_before),
+        );
+        let due_query = RangeQuery::new(Bound::Unbounded, Bound::Excluded(due_term));
+        queries.push((Occur::Must, Box::new(due_query)));
+    }
+    for tag in &filter.tags {
+        queries.push((Occur::Must, tag_query(&schema, tag)));
     }
 
-    Ok(())
+    let searcher = index.reader()?.searcher();
+    let limit = usize::try_from(searcher.num_docs())
+        .unwrap_or(usize::MAX)
+        .max(1);
+    let mut tasks: Vec<Task> = searcher
+        .search(&BooleanQuery::new(queries), &TopDocs::with_limit(limit))?
+        .into_iter()
+        .filter_map(|(_score, doc_address)| {
+            let doc: TantivyDocument = searcher.doc(doc_address).ok()?;
+            Task::from_tantivy_document(&doc, &schema)
+        })
+        .collect();
+    tasks.sort_by(|a, b| {
+        (a.due.is_none(), a.due, &a.path, a.line).cmp(&(b.due.is_none(), b.due, &b.path, b.line))
+    });
+    Ok(tasks)
+}
+
+/// Every note in the index, hidden ones included.
+///
+/// Callers fall back to the notes folder, so a stale index is not rebuilt.
+pub fn indexed_notes() -> tantivy::Result<Vec<Note>> {
+    let index = open_existing_index()?;
+    let schema = index.schema();
+    let note_query = kind_query(&schema, "note").unwrap_or_else(|| Box::new(AllQuery));
+
+    let searcher = index.reader()?.searcher();
+    let limit = usize::try_from(searcher.num_docs())
+        .unwrap_or(usize::MAX)
+        .max(1);
+    let notes = searcher
+        .search(&note_query, &TopDocs::with_limit(limit))?
+        .into_iter()
+        .filter_map(|(_score, doc_address)| {
+            let doc: TantivyDocument = searcher.doc(doc_address).ok()?;
+            Some(Note::from_tantivy_document(&doc, &schema))
+        })
+        .collect();
+    Ok(notes)
+}
+
+/// Notes whose title shares ngrams with `query`, best match first.
+pub fn typeahead_matches(query: &str, limit: usize) -> tantivy::Result<Vec<(f32, Note)>> {
+    let index = open_index()?;
+    let schema = index.schema();
+    let typeahead_field = schema.get_field("typeahead_title")?;
+
+    // The ngram tokens all share a position, so a phrase query cannot be used
+    let mut analyzer = index.tokenizer_for_field(typeahead_field)?;
+    let mut token_stream = analyzer.token_stream(query);
+    let mut title_queries: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    while token_stream.advance() {
+        let term = Term::from_field_text(typeahead_field, &token_stream.token().text);
+        title_queries.push((
+            Occur::Should,
+            Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs)),
+        ));
+    }
+    if title_queries.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut queries: Vec<(Occur, Box<dyn Query>)> =
+        vec![(Occur::Must, Box::new(BooleanQuery::new(title_queries)))];
+    if let Some(note_kind_query) = kind_query(&schema, "note") {
+        queries.push((Occur::Must, note_kind_query));
+    }
+
+    let searcher = index.reader()?.searcher();
+    let matches = searcher
+        .search(&BooleanQuery::new(queries), &TopDocs::with_limit(limit))?
+        .into_iter()
+        .filter_map(|(score, doc_address)| {
+            let doc: TantivyDocument = searcher.doc(doc_address).ok()?;
+            Some((score, Note::from_tantivy_document(&doc, &schema)))
+        })
+        .collect();
+    Ok(matches)
 }
 
 fn get_datetime_top_docs(
@@ -1,3 +1,5 @@
+use crate::error::InkError;
+use crate::frontmatter::FrontMatterFormat;
 use crate::utils::expand_tilde;
 use config::{Config, ConfigError, Environment, File, FileFormat};
 use globset::{Glob, GlobSetBuilder};
@@ -14,20 +16,60 @@ pub struct Settings {
     pub notes_dir: String,
     pub ignore: Vec<String>,
     pub note_template: Option<String>,
+    #[serde(default)]
+    pub frontmatter_format: FrontMatterFormat,
+    /// Heading level used as the title of notes without a frontmatter title, 0 to disable
+    pub title_heading_level: u8,
+    /// Let commands that only read notes read ones that are not valid UTF-8,
+    /// replacing the invalid bytes
+    #[serde(default)]
+    pub lossy_utf8: bool,
+    #[serde(default)]
+    pub http: HttpSettings,
+}
+
+/// How bookmark pages are fetched, from the `[http]` section of the config.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HttpSettings {
+    /// Seconds to wait for a response
+    pub timeout: u64,
+    pub user_agent: String,
+    /// Send every request through this proxy, eg `http://proxy.internal:3128`
+    pub proxy: Option<String>,
+    /// The largest page body to read, in bytes
+    pub max_body_size: u64,
+    /// How many redirects to follow before giving up
+    pub redirect_limit: usize,
+    /// Never make network requests
+    pub offline: bool,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        HttpSettings {
+            timeout: 15,
+            user_agent: concat!("ink/", env!("CARGO_PKG_VERSION")).to_string(),
+            proxy: None,
+            max_body_size: 5 * 1024 * 1024,
+            redirect_limit: 10,
+            offline: false,
+        }
+    }
 }
 
 impl Settings {
     pub fn new() -> Result<Self, ConfigError> {
         let default_config = include_str!("../settings/config/default.toml");
 
-        let cache_dir_buf = get_cache_dir();
+        let cache_dir_buf = get_cache_dir()?;
         let cache_dir = cache_dir_buf.to_str().ok_or_else(|| {
             ConfigError::Message(
                 "Config file path contains invalid Unicode characters.".to_string(),
             )
         })?;
 
-        let config_file_buf = get_config_file();
+        let config_file_buf = get_config_file()?;
         let config_file = config_file_buf.to_str().ok_or_else(|| {
             ConfigError::Message(
                 "Config file path contains invalid Unicode characters.".to_string(),
@@ -55,13 +97,21 @@ impl Settings {
     pub fn get_note_template_path(&self) -> Option<PathBuf> {
         self.note_template.as_ref().map(|dir| expand_tilde(dir))
     }
-    pub fn get_note_template_content(&self) -> String {
-        match self.get_note_template_path() {
-            Some(note_template_path_str) => {
-                fs::read_to_string(note_template_path_str).expect("oops")
-            }
-            None => include_str!("../settings/config/default-note.template.md").to_string(),
-        }
+    pub fn get_note_template_content(&self) -> Result<String, InkError> {
+        let Some(note_template_path) = self.get_note_template_path() else {
+            return Ok(match self.frontmatter_format {
+                FrontMatterFormat::Yaml => {
+                    include_str!("../settings/config/default-note.template.md").to_string()
+                }
+                FrontMatterFormat::Toml => {
+                    include_str!("../settings/config/default-note-toml.template.md").to_string()
+                }
+                FrontMatterFormat::Json => {
+                    include_str!("../settings/config/default-note-json.template.md").to_string()
+                }
+            });
+        };
+        fs::read_to_string(&note_template_path).map_err(InkError::io(&note_template_path))
     }
 
     pub fn is_path_ignored(&self, path: &Path) -> bool {
@@ -81,28 +131,28 @@ impl Settings {
     }
 }
 
-fn get_config_file() -> PathBuf {
+fn home_dir() -> Result<PathBuf, ConfigError> {
+    env::var("HOME")
+        .map(PathBuf::from)
+        .map_err(|_| ConfigError::Message("HOME directory not found".to_string()))
+}
+
+fn get_config_file() -> Result<PathBuf, ConfigError> {
     match env::var("XDG_CONFIG_HOME") {
-        Ok(path) => PathBuf::from(path).join("ink/ink.toml"),
-        Err(_) => env::var("HOME").map_or_else(
-            |_| panic!("HOME directory not found"),
-            |p| PathBuf::from(p).join(".config/ink/ink.toml"),
-        ),
+        Ok(path) => Ok(PathBuf::from(path).join("ink/ink.toml")),
+        Err(_) => Ok(home_dir()?.join(".config/ink/ink.toml")),
     }
 }
 
-fn get_cache_dir() -> PathBuf {
+fn get_cache_dir() -> Result<PathBuf, ConfigError> {
     match env::var("XDG_CACHE_HOME") {
-        Ok(path) => PathBuf::from(path).join("ink"),
-        Err(_) => env::var("HOME").map_or_else(
-            |_| panic!("HOME directory not found"),
-            |p| PathBuf::from(p).join(".cache/ink"),
-        ),
+        Ok(path) => Ok(PathBuf::from(path).join("ink")),
+        Err(_) => Ok(home_dir()?.join(".cache/ink")),
     }
 }
 
 pub static SETTINGS: std::sync::LazyLock<Settings> =
-    std::sync::LazyLock::new(|| Settings::new().expect("failred to load configuration"));
+    std::sync::LazyLock::new(|| Settings::new().unwrap_or_else(|e| InkError::from(e).exit()));
 
 #[cfg(test)]
 mod tests {
@@ -116,6 +166,10 @@ mod tests {
             notes_dir: "~/notes".to_string(),
             ignore: ignore_patterns,
             note_template: None,
+            frontmatter_format: FrontMatterFormat::Yaml,
+            title_heading_level: 1,
+            lossy_utf8: false,
+            http: HttpSettings::default(),
         }
     }
 
@@ -0,0 +1,373 @@
+use crate::cli::ColorChoice;
+use crate::error::InkError;
+use crate::markdown::{
+    frontmatter, get_markdown_str_read_only, heading_title, markdown, parser_options,
+    with_title_heading,
+};
+use crate::resolve::resolve_note;
+use crate::settings::SETTINGS;
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, LinkType, Parser, Tag, TagEnd};
+use std::io::IsTerminal;
+
+const RESET: &str = "\x1b[0m";
+const HEADING: &str = "\x1b[1;35m";
+const STRONG: &str = "\x1b[1m";
+const EMPHASIS: &str = "\x1b[3m";
+const STRIKETHROUGH: &str = "\x1b[9m";
+const CODE: &str = "\x1b[36m";
+const LINK: &str = "\x1b[4;34m";
+const DIM: &str = "\x1b[2m";
+
+/// Renders markdown events as styled terminal text.
+struct AnsiRenderer {
+    output: String,
+    color: bool,
+    styles: Vec<&'static str>,
+    /// The next number of each open list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    links: Vec<(LinkType, String)>,
+    quote_depth: usize,
+    in_code_block: bool,
+    at_line_start: bool,
+}
+
+impl AnsiRenderer {
+    fn new(color: bool) -> Self {
+        AnsiRenderer {
+            output: String::new(),
+            color,
+            styles: Vec::new(),
+            lists: Vec::new(),
+            links: Vec::new(),
+            quote_depth: 0,
+            in_code_block: false,
+            at_line_start: true,
+        }
+    }
+
+    fn push_style(&mut self, style: &'static str) {
+        self.styles.push(style);
+        if self.color {
+            self.output.push_str(style);
+        }
+    }
+
+    fn pop_style(&mut self) {
+        self.styles.pop();
+        if self.color {
+            self.output.push_str(RESET);
+            for style in &self.styles {
+                self.output.push_str(style);
+            }
+        }
+    }
+
+    fn styled(&mut self, style: &'static str, text: &str) {
+        self.push_style(style);
+        self.write(text);
+        self.pop_style();
+    }
+
+    /// Quote bars and list indentation for the start of a line.
+    fn write_prefix(&mut self, list_indent: usize) {
+        self.at_line_start = false;
+        let bars = "│ ".repeat(self.quote_depth);
+        if !bars.is_empty() {
+            self.styled(DIM, &bars);
+        }
+        self.output.push_str(&"  ".repeat(list_indent));
+    }
+
+    fn write(&mut self, text: &str) {
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.newline();
+            }
+            if line.is_empty() {
+                continue;
+            }
+            if self.at_line_start {
+                let code_indent = if self.in_code_block { 2 } else { 0 };
+                self.write_prefix(self.lists.len() + code_indent);
+            }
+            self.output.push_str(line);
+        }
+    }
+
+    fn newline(&mut self) {
+        self.output.push('\n');
+        self.at_line_start = true;
+    }
+
+    fn end_block(&mut self) {
+        if !self.at_line_start {
+            self.newline();
+        }
+        if self.lists.is_empty() {
+            self.newline();
+        }
+    }
+
+    fn start(&mut self, tag: Tag) {
+        match tag {
+            Tag::Heading { level, .. } => {
+                let hashes = "#".repeat(heading_depth(level));
+                self.push_style(HEADING);
+                self.write(&format!("{hashes} "));
+            }
+            Tag::BlockQuote(_) => self.quote_depth += 1,
+            Tag::CodeBlock(kind) => {
+                if let CodeBlockKind::Fenced(info) = kind {
+                    if let Some(lang) = info.split_whitespace().next() {
+                        self.styled(DIM, lang);
+                        self.newline();
+                    }
+                }
+                self.in_code_block = true;
+                self.push_style(CODE);
+            }
+            Tag::List(start) => {
+                if !self.at_line_start {
+                    self.newline();
+                }
+                self.lists.push(start);
+            }
+            Tag::Item => {
+                let bullet = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                };
+                self.write_prefix(self.lists.len().saturating_sub(1));
+                self.output.push_str(&bullet);
+            }
+            Tag::Emphasis => self.push_style(EMPHASIS),
+            Tag::Strong => self.push_style(STRONG),
+            Tag::Strikethrough => self.push_style(STRIKETHROUGH),
+            Tag::Link {
+                link_type,
+                dest_url,
+                ..
+            } => {
+                self.links.push((link_type, dest_url.to_string()));
+                self.push_style(LINK);
+            }
+            Tag::Image { .. } => {
+                self.push_style(DIM);
+                self.write("[image: ");
+            }
+            _ => {}
+        }
+    }
+
+    fn end(&mut self, tag: TagEnd) {
+        match tag {
+            TagEnd::Heading(_) => {
+                self.pop_style();
+                self.end_block();
+            }
+            TagEnd::Paragraph | TagEnd::Table => self.end_block(),
+            TagEnd::BlockQuote(_) => self.quote_depth = self.quote_depth.saturating_sub(1),
+            TagEnd::CodeBlock => {
+                self.pop_style();
+                self.in_code_block = false;
+                self.end_block();
+            }
+            TagEnd::List(_) => {
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.end_block();
+                }
+            }
+            TagEnd::Item if !self.at_line_start => self.newline(),
+            TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough => self.pop_style(),
+            TagEnd::Link => {
+                self.pop_style();
+                if let Some((link_type, url)) = self.links.pop() {
+                    let shows_url = matches!(
+                        link_type,
+                        LinkType::Autolink | LinkType::Email | LinkType::WikiLink { .. }
+                    );
+                    if !shows_url {
+                        self.styled(DIM, &format!(" ({url})"));
+                    }
+                }
+            }
+            TagEnd::Image => {
+                self.write("]");
+                self.pop_style();
+            }
+            TagEnd::TableHead | TagEnd::TableRow => self.newline(),
+            TagEnd::TableCell => self.write(" │ "),
+            _ => {}
+        }
+    }
+
+    fn render(mut self, markdown_input: &str) -> String {
+        for event in Parser::new_ext(markdown_input, parser_options()) {
+            match event {
+                Event::Start(tag) => self.start(tag),
+                Event::End(tag) => self.end(tag),
+                Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => {
+                    self.write(&text);
+                }
+                Event::Code(code) => {
+                    let code = if self.color {
+                        code.to_string()
+                    } else {
+                        format!("`{code}`")
+                    };
+                    self.styled(CODE, &code);
+                }
+                Event::SoftBreak | Event::HardBreak => self.newline(),
+                Event::Rule => {
+                    self.styled(DIM, &"─".repeat(40));
+                    self.end_block();
+                }
+                Event::TaskListMarker(done) => self.write(if done { "[x] " } else { "[ ] " }),
+                Event::FootnoteReference(name) => self.write(&format!("[^{name}]")),
+                _ => {}
+            }
+        }
+        let trimmed_len = self.output.trim_end().len();
+        self.output.truncate(trimmed_len);
+        self.output.push('\n');
+        self.output
+    }
+}
+
+fn heading_depth(level: HeadingLevel) -> usize {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// Render markdown with ANSI styling, or as plain text when `color` is false.
+pub fn render_ansi(markdown_input: &str, color: bool) -> String {
+    AnsiRenderer::new(color).render(markdown_input)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render markdown as a standalone HTML page.
+pub fn render_html_page(title: &str, markdown_input: &str) -> String {
+    format!(
+        concat!(
+            "<!DOCTYPE html>\n",
+            "<html>\n",
+            "<head>\n",
+            "<meta charset=\"utf-8\">\n",
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n",
+            "<title>{}</title>\n",
+            "<style>body {{ max-width: 42rem; margin: 2rem auto; padding: 0 1rem; ",
+            "font-family: sans-serif; line-height: 1.5; }} ",
+            "pre {{ overflow-x: auto; padding: 0.5rem; background: #f4f4f4; }}</style>\n",
+            "</head>\n",
+            "<body>\n",
+            "{}",
+            "</body>\n",
+            "</html>\n",
+        ),
+        escape_html(title),
+        markdown(markdown_input)
+    )
+}
+
+fn use_color(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::env::var_os("NO_COLOR").is_none() && std::io::stdout().is_terminal()
+        }
+    }
+}
+
+/// Print a note to the terminal, or as an HTML page.
+pub fn show(note: &str, is_html: bool, color: ColorChoice) -> Result<(), InkError> {
+    let path = resolve_note(note)?;
+    let parsed = frontmatter(&get_markdown_str_read_only(&path)?);
+    let title = parsed.title.unwrap_or_else(|| {
+        heading_title(&parsed.content, SETTINGS.title_heading_level).unwrap_or_else(|| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+    });
+    let content = with_title_heading(&title, &parsed.content);
+
+    if is_html {
+        print!("{}", render_html_page(&title, &content));
+    } else {
+        print!("{}", render_ansi(&content, use_color(color)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE: &str = concat!(
+        "# Title\n\n",
+        "Some *emphasis*, **strong** and `code` with a [link](https://example.com) and [[Other]].\n\n",
+        "- one\n",
+        "- [x] done\n",
+        "  1. nested\n\n",
+        "> quoted\n\n",
+        "```rust\n",
+        "fn main() {}\n",
+        "```\n",
+    );
+
+    #[test]
+    fn test_render_plain() {
+        assert_eq!(
+            render_ansi(NOTE, false),
+            concat!(
+                "# Title\n\n",
+                "Some emphasis, strong and `code` with a link (https://example.com) and Other.\n\n",
+                "• one\n",
+                "• [x] done\n",
+                "  1. nested\n\n",
+                "│ quoted\n\n",
+                "rust\n",
+                "    fn main() {}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_ansi_styles() {
+        let rendered = render_ansi(NOTE, true);
+        assert!(rendered.starts_with(&format!("{HEADING}# Title{RESET}\n")));
+        assert!(rendered.contains(&format!("{EMPHASIS}emphasis{RESET}")));
+        assert!(rendered.contains(&format!("{STRONG}strong{RESET}")));
+        assert!(rendered.contains(&format!("{CODE}code{RESET}")));
+        assert!(rendered.contains(&format!(
+            "{LINK}link{RESET}{DIM} (https://example.com){RESET}"
+        )));
+        assert!(rendered.contains(&format!("{CODE}    fn main() {{}}")));
+    }
+
+    #[test]
+    fn test_render_html_page() {
+        let page = render_html_page("Q&A", "# Q&A\n\nText");
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<title>Q&amp;A</title>"));
+        assert!(page.contains("<h1>Q&amp;A</h1>\n<p>Text</p>"));
+        assert!(page.trim_end().ends_with("</html>"));
+    }
+}
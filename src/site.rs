@@ -0,0 +1,325 @@
+use crate::error::InkError;
+use crate::links::{
+    markdown_target, normalize_path, note_id, scan_vault, wiki_target, Link, LinkKind,
+    LinkResolver, LinkedNote,
+};
+use crate::markdown::{parse_body, parser_options, with_title_heading};
+use crate::settings::SETTINGS;
+use crate::template::site_environment;
+use crate::utils::slugify;
+use minijinja::context;
+use pulldown_cmark::{html, CowStr, Event, LinkType, Parser, Tag, TagEnd};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Serialize)]
+struct PageLink {
+    title: String,
+    url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct TagLink {
+    name: String,
+    url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct NotePage {
+    title: String,
+    content: String,
+    tags: Vec<TagLink>,
+    backlinks: Vec<PageLink>,
+}
+
+#[derive(Debug, Serialize)]
+struct TagPage {
+    name: String,
+    url: String,
+    notes: Vec<PageLink>,
+}
+
+/// An entry of `search.json`, for client-side search.
+#[derive(Debug, Serialize)]
+struct SearchEntry {
+    id: String,
+    title: String,
+    url: String,
+    tags: Vec<String>,
+    text: String,
+}
+
+/// Note pages live under `notes/`, so a note called `index` cannot replace the site index.
+fn page_url(id: &str) -> String {
+    format!("notes/{id}.html")
+}
+
+fn tag_url(tag: &str) -> String {
+    format!("tags/{}.html", slugify(tag))
+}
+
+/// The relative path back to the site root from a page.
+fn root_prefix(page_path: &str) -> String {
+    "../".repeat(page_path.matches('/').count())
+}
+
+fn internal_link(link_type: LinkType, dest_url: &str) -> Option<Link> {
+    let (target, kind) = match link_type {
+        LinkType::WikiLink { .. } => (wiki_target(dest_url)?, LinkKind::Wiki),
+        _ => (markdown_target(dest_url)?, LinkKind::Markdown),
+    };
+    Some(Link {
+        target,
+        line: 0,
+        kind,
+    })
+}
+
+/// Render a note body to HTML, pointing internal links at the exported pages.
+///
+/// Links to notes that are missing or hidden are rendered as text.
+fn render_content(
+    content: &str,
+    source: &Path,
+    root: &str,
+    resolver: &LinkResolver,
+    page_ids: &HashMap<PathBuf, String>,
+) -> String {
+    let mut missing_links: Vec<bool> = Vec::new();
+    let events = Parser::new_ext(content, parser_options()).map(|event| match event {
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => {
+            let Some(link) = internal_link(link_type, &dest_url) else {
+                missing_links.push(false);
+                return Event::Start(Tag::Link {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                });
+            };
+            if let Some(target_id) = resolver
+                .resolve(source, &link)
+                .and_then(|path| page_ids.get(&path))
+            {
+                missing_links.push(false);
+                Event::Start(Tag::Link {
+                    link_type,
+                    dest_url: CowStr::from(format!("{root}{}", page_url(target_id))),
+                    title,
+                    id,
+                })
+            } else {
+                missing_links.push(true);
+                Event::InlineHtml(CowStr::from("<span class=\"missing\">"))
+            }
+        }
+        Event::End(TagEnd::Link) if missing_links.pop() == Some(true) => {
+            Event::InlineHtml(CowStr::from("</span>"))
+        }
+        other => other,
+    });
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, events);
+    html_output
+}
+
+fn write_page(outdir: &Path, page_path: &str, html: &str) -> Result<(), InkError> {
+    let path = outdir.join(page_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(InkError::io(parent))?;
+    }
+    fs::write(&path, html).map_err(InkError::io(&path))
+}
+
+fn page_link(linked_note: &LinkedNote, page_ids: &HashMap<PathBuf, String>) -> PageLink {
+    PageLink {
+        title: linked_note.note.title.clone(),
+        url: page_url(&page_ids[&linked_note.path]),
+    }
+}
+
+type Backlinks = HashMap<PathBuf, Vec<PageLink>>;
+type TaggedNotes = BTreeMap<String, Vec<PageLink>>;
+
+/// Collect the pages linking to each page, and the pages with each tag.
+fn collect_backlinks_and_tags(
+    linked_notes: &[LinkedNote],
+    resolver: &LinkResolver,
+    page_ids: &HashMap<PathBuf, String>,
+) -> (Backlinks, TaggedNotes) {
+    let mut backlinks = Backlinks::new();
+    let mut tags = TaggedNotes::new();
+    for linked_note in linked_notes {
+        let mut targets: Vec<PathBuf> = linked_note
+            .links
+            .iter()
+            .filter_map(|link| resolver.resolve(&linked_note.path, link))
+            .filter(|target| *target != linked_note.path && page_ids.contains_key(target))
+            .collect();
+        targets.sort();
+        targets.dedup();
+        for target in targets {
+            backlinks
+                .entry(target)
+                .or_default()
+                .push(page_link(linked_note, page_ids));
+        }
+        for tag in &linked_note.note.tags {
+            tags.entry(tag.clone())
+                .or_default()
+                .push(page_link(linked_note, page_ids));
+        }
+    }
+    (backlinks, tags)
+}
+
+/// Render every visible note, a page per tag, an index and `search.json` into `outdir`.
+pub fn export_site(outdir: &Path) -> Result<usize, InkError> {
+    let notes_root = normalize_path(&SETTINGS.get_notes_path());
+    let (mut linked_notes, resolver) = scan_vault(&notes_root)?;
+    linked_notes.retain(|linked_note| !linked_note.note.is_hidden_with_settings(&SETTINGS));
+    linked_notes.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let page_ids: HashMap<PathBuf, String> = linked_notes
+        .iter()
+        .map(|linked_note| {
+            (
+                linked_note.path.clone(),
+                note_id(&notes_root, &linked_note.path),
+            )
+        })
+        .collect();
+    let (backlinks, tags) = collect_backlinks_and_tags(&linked_notes, &resolver, &page_ids);
+
+    let env = site_environment()?;
+    let mut search_entries = Vec::new();
+    for linked_note in &linked_notes {
+        let id = &page_ids[&linked_note.path];
+        let page_path = page_url(id);
+        let root = root_prefix(&page_path);
+        let note = &linked_note.note;
+        let body = note.body.as_deref().unwrap_or_default();
+
+        let mut note_tags: Vec<&String> = note.tags.iter().collect();
+        note_tags.sort();
+        let page = NotePage {
+            title: note.title.clone(),
+            content: render_content(
+                &with_title_heading(&note.title, body),
+                &linked_note.path,
+                &root,
+                &resolver,
+                &page_ids,
+            ),
+            tags: note_tags
+                .iter()
+                .map(|tag| TagLink {
+                    name: (*tag).clone(),
+                    url: tag_url(tag),
+                })
+                .collect(),
+            backlinks: backlinks
+                .get(&linked_note.path)
+                .cloned()
+                .unwrap_or_default(),
+        };
+        let html = env
+            .get_template("note.html")?
+            .render(context! { note => page, root => root })?;
+        write_page(outdir, &page_path, &html)?;
+
+        search_entries.push(SearchEntry {
+            id: id.clone(),
+            title: note.title.clone(),
+            url: page_path,
+            tags: note_tags.into_iter().cloned().collect(),
+            text: parse_body(body).text,
+        });
+    }
+
+    let tag_pages: Vec<TagPage> = tags
+        .into_iter()
+        .map(|(name, notes)| TagPage {
+            url: tag_url(&name),
+            name,
+            notes,
+        })
+        .collect();
+    for tag in &tag_pages {
+        let html = env
+            .get_template("tag.html")?
+            .render(context! { tag => tag, root => root_prefix(&tag.url) })?;
+        write_page(outdir, &tag.url, &html)?;
+    }
+
+    let notes: Vec<PageLink> = linked_notes
+        .iter()
+        .map(|linked_note| page_link(linked_note, &page_ids))
+        .collect();
+    let html = env
+        .get_template("index.html")?
+        .render(context! { notes => notes, tags => tag_pages, root => "" })?;
+    write_page(outdir, "index.html", &html)?;
+    write_page(
+        outdir,
+        "search.json",
+        &serde_json::to_string(&search_entries).unwrap(),
+    )?;
+
+    Ok(linked_notes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_root_prefix() {
+        assert_eq!(root_prefix("index.html"), "");
+        assert_eq!(root_prefix("tags/rust.html"), "../");
+        assert_eq!(root_prefix("a/b/c.html"), "../../");
+    }
+
+    #[test]
+    fn test_render_content_rewrites_internal_links() {
+        let root = PathBuf::from("/notes");
+        let paths = vec![
+            PathBuf::from("/notes/index.md"),
+            PathBuf::from("/notes/project/plan.md"),
+            PathBuf::from("/notes/secret.md"),
+        ];
+        let resolver = LinkResolver::new(&root, &paths);
+        // secret.md is hidden, so it has no page
+        let page_ids: HashMap<PathBuf, String> = [
+            (paths[0].clone(), "index".to_string()),
+            (paths[1].clone(), "project/plan".to_string()),
+        ]
+        .into_iter()
+        .collect();
+
+        let html = render_content(
+            "[[index|Home]] [up](../index.md) [[secret]] [[nowhere]] [web](https://example.com)",
+            &paths[1],
+            "../",
+            &resolver,
+            &page_ids,
+        );
+        assert_eq!(
+            html,
+            concat!(
+                "<p><a href=\"../notes/index.html\">Home</a> ",
+                "<a href=\"../notes/index.html\">up</a> ",
+                "<span class=\"missing\">secret</span> ",
+                "<span class=\"missing\">nowhere</span> ",
+                "<a href=\"https://example.com\">web</a></p>\n",
+            )
+        );
+    }
+}
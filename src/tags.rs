@@ -0,0 +1,101 @@
+use crate::error::InkError;
+use crate::frontmatter::{read_tags, update_tags};
+use crate::markdown::get_markdown_str;
+use crate::resolve::resolve_note;
+use crate::search::{find_notes, reindex_files};
+use crate::settings::SETTINGS;
+use crate::walk::{has_extension, walk_files};
+use std::fs;
+use std::path::PathBuf;
+
+fn normalize_tag(tag: &str) -> String {
+    tag.trim().trim_start_matches('#').to_string()
+}
+
+fn get_target_paths(note: &str, is_query: bool) -> Result<Vec<PathBuf>, InkError> {
+    if is_query {
+        let notes = find_notes(note, None, usize::MAX, true)?;
+        return Ok(notes.iter().map(crate::note::Note::get_file_path).collect());
+    }
+    Ok(vec![resolve_note(note)?])
+}
+
+/// Rewrite the tags of each file, then re-index the files that changed.
+///
+/// Stops at the first file that cannot be read or written.
+fn rewrite_tags<F>(paths: &[PathBuf], update: F) -> Result<(), InkError>
+where
+    F: Fn(Vec<String>) -> Vec<String>,
+{
+    let mut changed = Vec::new();
+    let mut result = Ok(());
+    for path in paths {
+        let raw_markdown = match get_markdown_str(path) {
+            Ok(raw_markdown) => raw_markdown,
+            Err(e) => {
+                result = Err(e);
+                break;
+            }
+        };
+        let updated_markdown = update_tags(&raw_markdown, SETTINGS.frontmatter_format, &update);
+        if updated_markdown == raw_markdown {
+            continue;
+        }
+        if let Err(e) = fs::write(path, updated_markdown) {
+            result = Err(InkError::io(path)(e));
+            break;
+        }
+        println!("Updated {}", path.display());
+        changed.push(path.clone());
+    }
+
+    if !changed.is_empty() {
+        if let Err(e) = reindex_files(&changed) {
+            eprintln!("Could not update the index {e}");
+        }
+    }
+    result
+}
+
+pub fn add_tags(note: &str, tags: &[String], is_query: bool) -> Result<(), InkError> {
+    let new_tags: Vec<String> = tags.iter().map(|tag| normalize_tag(tag)).collect();
+    rewrite_tags(&get_target_paths(note, is_query)?, |mut existing| {
+        for tag in &new_tags {
+            if !existing.contains(tag) {
+                existing.push(tag.clone());
+            }
+        }
+        existing
+    })
+}
+
+pub fn remove_tags(note: &str, tags: &[String], is_query: bool) -> Result<(), InkError> {
+    let old_tags: Vec<String> = tags.iter().map(|tag| normalize_tag(tag)).collect();
+    rewrite_tags(&get_target_paths(note, is_query)?, |mut existing| {
+        existing.retain(|tag| !old_tags.contains(tag));
+        existing
+    })
+}
+
+/// Rename a tag in every note in the notes folder.
+pub fn rename_tag(old: &str, new: &str) -> Result<(), InkError> {
+    let (old, new) = (normalize_tag(old), normalize_tag(new));
+    let mut paths = Vec::new();
+    walk_files(&SETTINGS.get_notes_path(), true, has_extension, |path| {
+        if read_tags(&get_markdown_str(path)?).contains(&old) {
+            paths.push(path.to_path_buf());
+        }
+        Ok(())
+    })?;
+
+    rewrite_tags(&paths, |existing| {
+        let mut renamed: Vec<String> = Vec::with_capacity(existing.len());
+        for tag in existing {
+            let tag = if tag == old { new.clone() } else { tag };
+            if !renamed.contains(&tag) {
+                renamed.push(tag);
+            }
+        }
+        renamed
+    })
+}
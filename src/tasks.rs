@@ -0,0 +1,288 @@
+use crate::error::InkError;
+use crate::frontmatter::frontmatter_line_count;
+use crate::links::{normalize_path, note_id};
+use crate::markdown::get_markdown_str;
+use crate::resolve::resolve_note;
+use crate::search::{find_tasks, reindex_files};
+use crate::settings::SETTINGS;
+use chrono::NaiveDate;
+use regex::Regex;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tantivy::schema::document::Value;
+use tantivy::schema::{Facet, Schema, TantivyDocument as Document};
+use tantivy::DateTime as tantivy_DateTime;
+
+static TASK_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+\[([ xX])\]\s+(.*?)\s*$").unwrap());
+static DUE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"@due\((\d{4}-\d{2}-\d{2})\)").unwrap());
+static TAG_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?:^|\s)#([\w/-]+)").unwrap());
+
+/// A `- [ ]` checklist item found in a note.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Task {
+    /// `<note id>:<line>`, where the note id is its path relative to the notes folder
+    pub id: String,
+    pub path: PathBuf,
+    /// 1-based line number within the file
+    pub line: usize,
+    pub text: String,
+    pub done: bool,
+    pub due: Option<NaiveDate>,
+    /// Inline `#tag` markers
+    pub tags: Vec<String>,
+}
+
+/// Filters for `ink tasks`, all of which must match.
+#[derive(Debug, Default)]
+pub struct TaskFilter {
+    pub done: Option<bool>,
+    pub due_before: Option<NaiveDate>,
+    pub tags: Vec<String>,
+    pub include_ignored: bool,
+}
+
+impl TaskFilter {
+    pub fn from_flags(
+        open: bool,
+        done: bool,
+        due_before: Option<NaiveDate>,
+        tags: &[String],
+        include_ignored: bool,
+    ) -> Self {
+        TaskFilter {
+            done: match (open, done) {
+                (true, _) => Some(false),
+                (_, true) => Some(true),
+                _ => None,
+            },
+            due_before,
+            tags: tags
+                .iter()
+                .map(|tag| tag.trim_start_matches('#').to_string())
+                .collect(),
+            include_ignored,
+        }
+    }
+}
+
+fn parse_task_line(line: &str) -> Option<(bool, String)> {
+    let captures = TASK_RE.captures(line)?;
+    let text = captures[2].to_string();
+    if text.is_empty() {
+        return None;
+    }
+    Some((&captures[1] != " ", text))
+}
+
+/// Find the tasks in a note, skipping frontmatter and fenced code blocks.
+pub fn extract_tasks(raw_markdown: &str, notes_root: &Path, path: &Path) -> Vec<Task> {
+    let id = note_id(notes_root, path);
+    let skip = frontmatter_line_count(raw_markdown);
+    let mut tasks = Vec::new();
+    let mut in_fence = false;
+
+    for (index, line) in raw_markdown.lines().enumerate().skip(skip) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let Some((done, text)) = parse_task_line(line) else {
+            continue;
+        };
+        let due = DUE_RE
+            .captures(&text)
+            .and_then(|captures| NaiveDate::parse_from_str(&captures[1], "%Y-%m-%d").ok());
+        let tags = TAG_RE
+            .captures_iter(&text)
+            .map(|captures| captures[1].to_string())
+            .collect();
+        tasks.push(Task {
+            id: format!("{id}:{}", index + 1),
+            path: path.to_path_buf(),
+            line: index + 1,
+            text,
+            done,
+            due,
+            tags,
+        });
+    }
+    tasks
+}
+
+/// Due dates are indexed as midnight UTC.
+pub fn date_to_tantivy(date: NaiveDate) -> tantivy_DateTime {
+    let timestamp = date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp();
+    tantivy_DateTime::from_timestamp_secs(timestamp)
+}
+
+impl Task {
+    pub fn to_tantivy_document(&self, schema: &Schema, is_hidden: bool) -> Document {
+        let mut doc = Document::new();
+        doc.add_text(schema.get_field("kind").unwrap(), "task");
+        doc.add_text(schema.get_field("task_id").unwrap(), &self.id);
+        doc.add_text(schema.get_field("title").unwrap(), &self.text);
+        doc.add_text(schema.get_field("body").unwrap(), &self.text);
+        doc.add_text(
+            schema.get_field("path").unwrap(),
+            self.path.to_string_lossy(),
+        );
+        doc.add_u64(schema.get_field("line").unwrap(), self.line as u64);
+        doc.add_bool(schema.get_field("done").unwrap(), self.done);
+        doc.add_bool(schema.get_field("is_hidden").unwrap(), is_hidden);
+        if let Some(due) = self.due {
+            doc.add_date(schema.get_field("due").unwrap(), date_to_tantivy(due));
+        }
+        for tag in &self.tags {
+            let facet = Facet::from(&format!("/tag/{tag}"));
+            doc.add_facet(schema.get_field("tag").unwrap(), facet);
+        }
+        doc
+    }
+
+    pub fn from_tantivy_document(document: &Document, schema: &Schema) -> Option<Self> {
+        let text_value = |name: &str| {
+            document
+                .get_first(schema.get_field(name).ok()?)
+                .and_then(|value| value.as_str())
+                .map(str::to_string)
+        };
+        let line = document
+            .get_first(schema.get_field("line").ok()?)
+            .and_then(|value| value.as_u64())?;
+        let done = document
+            .get_first(schema.get_field("done").ok()?)
+            .and_then(|value| value.as_bool())?;
+        let due = document
+            .get_first(schema.get_field("due").ok()?)
+            .and_then(|value| value.as_datetime())
+            .and_then(|date| chrono::DateTime::from_timestamp(date.into_timestamp_secs(), 0))
+            .map(|date| date.date_naive());
+        let tags = document
+            .get_all(schema.get_field("tag").ok()?)
+            .filter_map(|value| value.as_facet())
+            .filter_map(|encoded| Facet::from_encoded(encoded.as_bytes().to_vec()).ok())
+            .map(|facet| facet.to_string().replace("/tag/", ""))
+            .collect();
+        Some(Task {
+            id: text_value("task_id")?,
+            path: PathBuf::from(text_value("path")?),
+            line: usize::try_from(line).ok()?,
+            text: text_value("title")?,
+            done,
+            due,
+            tags,
+        })
+    }
+}
+
+/// List the indexed tasks matching `filter`, earliest due date first.
+pub fn list_tasks(filter: &TaskFilter, is_json: bool) -> Result<(), InkError> {
+    let tasks = find_tasks(filter)?;
+    if is_json {
+        println!("{}", serde_json::to_string(&tasks).unwrap());
+        return Ok(());
+    }
+    for task in &tasks {
+        let check = if task.done { 'x' } else { ' ' };
+        println!("{}\t[{check}] {}", task.id, task.text);
+    }
+    Ok(())
+}
+
+/// Tick the checkbox of a task in its source file. Fails when the task cannot be found.
+pub fn complete_task(id: &str) -> Result<(), InkError> {
+    let Some((note, line)) = id
+        .rsplit_once(':')
+        .and_then(|(note, line)| Some((note, line.parse::<usize>().ok()?)))
+    else {
+        eprintln!("Invalid task id {id}, expected <note>:<line>");
+        return Err(InkError::Failed);
+    };
+    let path = resolve_note(note)?;
+    let raw_markdown = get_markdown_str(&path)?;
+    let mut lines: Vec<&str> = raw_markdown.split_inclusive('\n').collect();
+    let Some((done, text)) = line
+        .checked_sub(1)
+        .and_then(|index| lines.get(index))
+        .and_then(|source_line| parse_task_line(source_line))
+    else {
+        eprintln!(
+            "No task on line {line} of {}, try re-indexing",
+            path.display()
+        );
+        return Err(InkError::Failed);
+    };
+    if done {
+        println!("Already done: {text}");
+        return Ok(());
+    }
+
+    let ticked = lines[line - 1].replacen("[ ]", "[x]", 1);
+    lines[line - 1] = &ticked;
+    fs::write(&path, lines.concat()).map_err(InkError::io(&path))?;
+    println!("Done: {text}");
+
+    if let Err(e) = reindex_files(&[normalize_path(&path)]) {
+        eprintln!("Could not update the index {e}");
+    }
+    Ok(())
+}
+
+/// The task documents for a note, indexed alongside it.
+pub fn note_tasks(raw_markdown: &str, path: &Path) -> Vec<Task> {
+    extract_tasks(raw_markdown, &SETTINGS.get_notes_path(), path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_tasks() {
+        let markdown = "---\ntitle: Todo\ntags:\n    - [ ] not a task\n---\n# Todo\n\n- [ ] Write report @due(2026-10-20) #work\n* [x] Buy milk #home #errands\n1. [ ] Numbered\n```\n- [ ] in code\n```\n- [ ]\n- plain item\n";
+        let tasks = extract_tasks(
+            markdown,
+            Path::new("/notes"),
+            Path::new("/notes/projects/todo.md"),
+        );
+        assert_eq!(tasks.len(), 3);
+
+        assert_eq!(tasks[0].id, "projects/todo:8");
+        assert_eq!(tasks[0].line, 8);
+        assert_eq!(tasks[0].text, "Write report @due(2026-10-20) #work");
+        assert!(!tasks[0].done);
+        assert_eq!(tasks[0].due, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert_eq!(tasks[0].tags, vec!["work".to_string()]);
+
+        assert!(tasks[1].done);
+        assert_eq!(tasks[1].due, None);
+        assert_eq!(
+            tasks[1].tags,
+            vec!["home".to_string(), "errands".to_string()]
+        );
+
+        assert_eq!(tasks[2].text, "Numbered");
+        assert_eq!(tasks[2].line, 10);
+    }
+
+    #[test]
+    fn test_task_document_round_trip() {
+        let schema = crate::search::get_schema();
+        let task = extract_tasks(
+            "- [ ] Ship it @due(2026-01-02) #release\n",
+            Path::new("/notes"),
+            Path::new("/notes/plan.md"),
+        )
+        .remove(0);
+        let doc = task.to_tantivy_document(&schema, false);
+        assert_eq!(Task::from_tantivy_document(&doc, &schema), Some(task));
+    }
+}
@@ -1,9 +1,10 @@
+use crate::error::InkError;
 use crate::note::Note;
 use crate::settings::SETTINGS;
 use minijinja::value::Value;
 use minijinja::{context, Environment};
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 
 fn get_note_context(note: &Note) -> Value {
     let ctx = context! {
@@ -12,14 +13,34 @@ fn get_note_context(note: &Note) -> Value {
     ctx
 }
 
-pub fn render_note(file_path: PathBuf, note: &Note) -> Result<(), Box<dyn std::error::Error>> {
+/// A double quoted string, valid in YAML, TOML and JSON frontmatter.
+///
+/// Unlike `tojson`, this leaves `'`, `&`, `<` and `>` readable.
+fn quote(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+pub fn render_note(file_path: &Path, note: &Note) -> Result<(), InkError> {
     let mut env = Environment::new();
+    env.add_filter("quote", quote);
 
-    let template_content = SETTINGS.get_note_template_content();
+    let template_content = SETTINGS.get_note_template_content()?;
     env.add_template("note.md", &template_content)?;
-    let tmpl = env.get_template("note.md").unwrap();
+    let tmpl = env.get_template("note.md")?;
     let ctx = get_note_context(note);
     let rendered_template = tmpl.render(&ctx)?;
-    fs::write(file_path, rendered_template)?;
-    Ok(())
+    fs::write(file_path, rendered_template).map_err(InkError::io(file_path))
+}
+
+/// The page layouts used by `ink export site`.
+pub fn site_environment() -> Result<Environment<'static>, minijinja::Error> {
+    let mut env = Environment::new();
+    env.add_template("base.html", include_str!("settings/config/site/base.html"))?;
+    env.add_template("note.html", include_str!("settings/config/site/note.html"))?;
+    env.add_template("tag.html", include_str!("settings/config/site/tag.html"))?;
+    env.add_template(
+        "index.html",
+        include_str!("settings/config/site/index.html"),
+    )?;
+    Ok(env)
 }
@@ -33,6 +33,15 @@ pub fn slugify(text: &str) -> String {
     clean_slug.trim_matches('-').to_string()
 }
 
+/// Escape text for use in XML and HTML, inside elements and quoted attributes.
+pub fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
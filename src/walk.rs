@@ -1,28 +1,54 @@
-use std::path::PathBuf;
+use crate::error::InkError;
+use std::path::Path;
 use walkdir::{DirEntry, WalkDir};
 
 pub fn has_extension(entry: &DirEntry) -> bool {
-    let name = entry.file_name().to_str().unwrap();
-    std::path::Path::new(name)
+    entry
+        .path()
         .extension()
         .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
 }
 
-pub fn walk_files<F, R>(dir: &PathBuf, recurse_into: bool, filter: F, render: R)
+/// Call `render` with the path of every file under `dir` that passes `filter`.
+/// A file that cannot be read or parsed is skipped with a warning, and the number
+/// of skipped files is reported at the end. Any other error stops the walk.
+pub fn walk_files<F, R>(
+    dir: &Path,
+    recurse_into: bool,
+    filter: F,
+    mut render: R,
+) -> Result<(), InkError>
 where
     F: Fn(&DirEntry) -> bool,
-    R: Fn(&str),
+    R: FnMut(&Path) -> Result<(), InkError>,
 {
     let walker = if recurse_into {
         WalkDir::new(dir).max_depth(3)
     } else {
         WalkDir::new(dir).max_depth(1)
     };
-    for entry in walker.into_iter().filter_map(Result::ok) {
+    let mut skipped = 0;
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                eprintln!("Warning: Skipping {e}");
+                skipped += 1;
+                continue;
+            }
+        };
         if filter(&entry) {
-            if let Some(name) = entry.path().to_str() {
-                render(name);
+            match render(entry.path()) {
+                Err(e) if e.is_file_error() => {
+                    eprintln!("Warning: Skipping {e}");
+                    skipped += 1;
+                }
+                result => result?,
             }
         }
     }
+    if skipped > 0 {
+        eprintln!("Skipped {skipped} files that could not be read");
+    }
+    Ok(())
 }
@@ -1,30 +1,20 @@
+use crate::editor::open_in_editor;
+use crate::error::InkError;
 use crate::note::Note;
 use crate::prompt::ParsedQuery;
-use std::env;
-use std::process::Command;
 
-fn get_editor() -> String {
-    match env::var("EDITOR") {
-        Ok(value) => value,
-        Err(_) => String::from("vim"),
-    }
-}
-
-pub fn prompt(title: &str) -> Note {
+pub fn prompt(title: &str) -> Result<Note, InkError> {
     let parsed_prompt = ParsedQuery::from_query(title);
     let mut note = Note::from_parsed_prompt(parsed_prompt);
     note.add_tag("prompt".to_string());
 
     if !note.file_exists() {
-        note.render_new_note();
+        note.render_new_note()?;
     }
-    note
+    Ok(note)
 }
 
-pub fn prompt_and_edit(title: &str) {
-    let note = prompt(title);
-    let mut cmd = Command::new(get_editor());
-    cmd.arg(note.get_file_path())
-        .status()
-        .expect("Couldn't launch editor");
+pub fn prompt_and_edit(title: &str) -> Result<(), InkError> {
+    let note = prompt(title)?;
+    open_in_editor(&note.get_file_path(), None)
 }
@@ -0,0 +1,60 @@
+mod common;
+
+use common::Vault;
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html><head><title>Sourdough Guide</title></head>
+<body>
+<nav><a href="/">Home</a> <a href="/about">About</a></nav>
+<article>
+<h1>Sourdough Guide</h1>
+<p>Feed the <strong>starter</strong> with rye flour every morning.</p>
+<h2>Baking</h2>
+<ul><li>Preheat the dutch oven</li><li>Bake for 40 minutes</li></ul>
+</article>
+<footer>Subscribe to our newsletter</footer>
+</body></html>"#;
+
+/// `ink mark create --archive` stores the readable page text in the bookmark, searchably.
+#[test]
+fn test_mark_archive() {
+    let base_url = common::serve_html(PAGE);
+    let vault = Vault::new();
+
+    let url = format!("{base_url}/sourdough");
+    let created = vault.ink(&["mark", "create", &url, "--archive"]);
+    assert!(created.status.success());
+    let content = vault.read("sourdough-guide.md");
+    assert!(content.contains("\narchived: "));
+    let snapshot = content
+        .split_once("\n## Snapshot\n\n")
+        .expect("Missing snapshot section")
+        .1;
+    assert!(snapshot.starts_with(&format!("> Archived from <{url}> on ")));
+    assert!(snapshot.ends_with(concat!(
+        "### Sourdough Guide\n\n",
+        "Feed the **starter** with rye flour every morning.\n\n",
+        "#### Baking\n\n",
+        "- Preheat the dutch oven\n",
+        "- Bake for 40 minutes\n",
+    )));
+    assert!(!snapshot.contains("newsletter"));
+
+    let search = vault.ink(&["search", "rye"]);
+    assert!(common::stdout(&search).contains("sourdough-guide.md"));
+
+    // Archiving again replaces the snapshot rather than adding another
+    vault.write(
+        "sourdough-guide.md",
+        content.replace("## Snapshot", "My notes\n\n## Snapshot"),
+    );
+    let archived = vault.ink(&["mark", "archive", "Sourdough Guide"]);
+    assert!(archived.status.success());
+    let content = vault.read("sourdough-guide.md");
+    assert_eq!(content.matches("## Snapshot").count(), 1);
+    assert!(content.contains("My notes\n\n## Snapshot"));
+
+    vault.write("plain.md", "---\ntitle: Plain\n---\nText\n");
+    let not_bookmark = vault.ink(&["mark", "archive", "plain"]);
+    assert_eq!(not_bookmark.status.code(), Some(5));
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use common::Vault;
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html><head>
+<title>Rust &amp; Friends | The Blog</title>
+<meta property="og:title" content="Rust &amp; Friends">
+<meta property="og:description" content="Why it's &quot;fearless&quot;: ownership &amp; more">
+<meta property="og:site_name" content="The Blog">
+<meta name="author" content="Ferris">
+<meta property="article:published_time" content="2026-09-30">
+<link rel="canonical" href="/posts/rust-and-friends">
+<link rel="icon" href="/favicon.svg">
+</head><body><p>Hello</p></body></html>"#;
+
+/// `ink mark create` stores Open Graph metadata from the page in frontmatter.
+#[test]
+fn test_mark_create_extracts_metadata() {
+    let base_url = common::serve_html(PAGE);
+    let vault = Vault::new();
+
+    let url = format!("{base_url}/posts/rust?ref=feed");
+    let created = vault.ink(&["mark", "create", &url, "Worth a read"]);
+    assert!(created.status.success());
+    assert_eq!(common::stdout(&created).trim(), "Rust & Friends");
+
+    let content = vault.read("rust-friends.md");
+    assert_eq!(
+        content,
+        format!(
+            concat!(
+                "---\n",
+                "title: \"Rust & Friends\"\n",
+                "tags:\n",
+                "    - bookmark\n",
+                "url: {url}\n",
+                "description: \"Why it's \\\"fearless\\\": ownership & more\"\n",
+                "site_name: \"The Blog\"\n",
+                "author: \"Ferris\"\n",
+                "published: \"2026-09-30\"\n",
+                "canonical_url: \"{base}/posts/rust-and-friends\"\n",
+                "favicon_url: \"{base}/favicon.svg\"\n",
+                "---\n",
+                "Worth a read",
+            ),
+            url = url,
+            base = base_url
+        )
+    );
+
+    let listed = vault.ink(&["mark", "list", "--json"]);
+    let bookmarks: serde_json::Value = serde_json::from_slice(&listed.stdout).unwrap();
+    assert_eq!(bookmarks[0]["site_name"], "The Blog");
+    assert_eq!(
+        bookmarks[0]["description"],
+        "Why it's \"fearless\": ownership & more"
+    );
+}
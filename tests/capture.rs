@@ -0,0 +1,53 @@
+mod common;
+
+use common::Vault;
+
+/// `ink capture` turns stdin into a tagged note, and `ink append` adds entries to it.
+#[test]
+fn test_capture_and_append() {
+    let vault = Vault::new();
+    let note_path = vault.notes_dir.join("build-log.md");
+
+    let captured = vault.ink_with_stdin(&["capture", "Build log #ci"], "error: linker failed\n");
+    assert!(captured.status.success());
+    assert_eq!(
+        common::stdout(&captured).trim(),
+        note_path.to_str().unwrap()
+    );
+    let content = vault.read("build-log.md");
+    assert!(content.contains("title: \"Build log\""));
+    assert!(content.contains("- ci"));
+    assert!(content.ends_with("---\nerror: linker failed\n"));
+
+    let search = vault.ink(&["search", "linker"]);
+    assert!(common::stdout(&search).contains("build-log.md"));
+
+    let again = vault.ink_with_stdin(&["capture", "Build log"], "more");
+    assert_eq!(again.status.code(), Some(1));
+
+    let appended = vault.ink_with_stdin(
+        &[
+            "append",
+            "build log",
+            "Retried with lld",
+            "--under",
+            "Fixes",
+        ],
+        "",
+    );
+    assert!(appended.status.success());
+    let appended = vault.ink_with_stdin(&["append", "build-log", "-u", "fixes"], "It worked\n");
+    assert!(appended.status.success());
+    let content = vault.read("build-log.md");
+    let fixes = content
+        .split_once("error: linker failed\n\n## Fixes\n\n- ")
+        .expect("Missing Fixes section")
+        .1;
+    let entries: Vec<&str> = fixes.lines().collect();
+    assert_eq!(entries.len(), 2);
+    assert!(entries[0].ends_with(" Retried with lld"));
+    assert!(entries[1].starts_with("- ") && entries[1].ends_with(" It worked"));
+
+    let search = vault.ink(&["search", "lld"]);
+    assert!(common::stdout(&search).contains("build-log.md"));
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use common::Vault;
+
+/// `ink check links` should report unresolved links with their line number,
+/// list notes nothing links to, and exit non-zero so it can gate a pre-commit hook.
+#[test]
+fn test_check_links_reports_broken_links_and_orphans() {
+    let vault = Vault::new();
+    vault.write(
+        "index.md",
+        "# Index\n\nSee [[plan]] and [[missing-note]].\n",
+    );
+    vault.write(
+        "projects/plan.md",
+        "---\ntitle: Plan\n---\n\nBack to [index](../index.md), not [[old-plan]].\n",
+    );
+    vault.write("lonely.md", "Nobody links here.\n");
+
+    let output = vault.ink(&["check", "links", "--json"]);
+
+    assert_eq!(output.status.code(), Some(1), "Broken links should fail");
+
+    let report: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("Output should be valid json");
+
+    let broken_links = report["broken_links"].as_array().unwrap();
+    assert_eq!(broken_links.len(), 2);
+    let broken_link = |target: &str| {
+        broken_links
+            .iter()
+            .find(|link| link["target"] == target)
+            .unwrap()
+    };
+    assert_eq!(broken_link("missing-note")["line"], 3);
+    assert!(broken_link("missing-note")["path"]
+        .as_str()
+        .unwrap()
+        .ends_with("index.md"));
+    // Line numbers count the frontmatter
+    assert_eq!(broken_link("old-plan")["line"], 5);
+
+    let orphans = report["orphans"].as_array().unwrap();
+    assert_eq!(orphans.len(), 1);
+    assert!(orphans[0].as_str().unwrap().ends_with("lonely.md"));
+}
@@ -0,0 +1,177 @@
+// Each test crate uses only some of these helpers
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use tempfile::TempDir;
+
+/// A notes folder and search index in a temporary directory, for running
+/// `ink` against. The config folder points there too, so no user settings leak in.
+pub struct Vault {
+    root: TempDir,
+    pub notes_dir: PathBuf,
+    pub cache_dir: PathBuf,
+}
+
+impl Vault {
+    pub fn new() -> Self {
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let notes_dir = temp_dir.path().join("notes");
+        fs::create_dir_all(&notes_dir).expect("Failed to create notes directory");
+        let cache_dir = temp_dir.path().join("ink");
+        Vault {
+            root: temp_dir,
+            notes_dir,
+            cache_dir,
+        }
+    }
+
+    /// The temporary directory holding the notes, the index and the config.
+    pub fn path(&self) -> &Path {
+        self.root.path()
+    }
+
+    /// Where `ink` looks for `ink/ink.toml`.
+    pub fn config_dir(&self) -> PathBuf {
+        self.path().join("config")
+    }
+
+    /// Write the `ink.toml` the commands are run with.
+    pub fn write_config(&self, config: &str) {
+        let config_dir = self.config_dir().join("ink");
+        fs::create_dir_all(&config_dir).expect("Failed to create config directory");
+        fs::write(config_dir.join("ink.toml"), config).expect("Failed to write config");
+    }
+
+    /// Write a file relative to the notes folder, creating its folder.
+    pub fn write(&self, name: &str, content: impl AsRef<[u8]>) {
+        let path = self.notes_dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).expect("Failed to create note folder");
+        fs::write(&path, content).expect("Failed to write note");
+    }
+
+    /// Read a file relative to the notes folder.
+    pub fn read(&self, name: &str) -> String {
+        fs::read_to_string(self.notes_dir.join(name)).expect("Failed to read note")
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new("./target/debug/ink");
+        command
+            .args(args)
+            .env("INK_NOTES_DIR", &self.notes_dir)
+            .env("INK_CACHE_DIR", &self.cache_dir)
+            .env("XDG_CONFIG_HOME", self.config_dir());
+        command
+    }
+
+    /// Run `ink` against this vault.
+    pub fn ink(&self, args: &[&str]) -> Output {
+        self.command(args).output().expect("Failed to execute ink")
+    }
+
+    /// Run `ink` against this vault with one more environment variable set.
+    pub fn ink_with_env(&self, args: &[&str], key: &str, value: &str) -> Output {
+        self.command(args)
+            .env(key, value)
+            .output()
+            .expect("Failed to execute ink")
+    }
+
+    /// Run `ink` against this vault, which must succeed, and return its stdout.
+    pub fn ink_stdout(&self, args: &[&str]) -> String {
+        let output = self.ink(args);
+        assert!(
+            output.status.success(),
+            "ink {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        stdout(&output)
+    }
+
+    /// Run `ink` against this vault with `stdin` piped in.
+    pub fn ink_with_stdin(&self, args: &[&str], stdin: &str) -> Output {
+        let mut child = self
+            .command(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("Failed to execute ink");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(stdin.as_bytes())
+            .expect("Failed to write stdin");
+        child.wait_with_output().expect("Failed to wait for ink")
+    }
+}
+
+/// The stdout of a command, which must be UTF-8.
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).expect("stdout is not UTF-8")
+}
+
+/// The first tab-separated field of each line, such as the titles `ink search` prints.
+pub fn first_fields(stdout: &str) -> Vec<&str> {
+    stdout
+        .lines()
+        .map(|line| line.split('\t').next().unwrap())
+        .collect()
+}
+
+/// Serve `html` to every request on a local port, returning the base URL.
+pub fn serve_html(html: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind stub server");
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(&stream);
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+                line.clear();
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{html}",
+                html.len()
+            );
+            let _ = (&stream).write_all(response.as_bytes());
+        }
+    });
+    format!("http://{address}")
+}
+
+/// Answer each request with the status and optional `Location` header that
+/// `route` gives for its method and path, returning the base URL.
+pub fn serve_routes(route: fn(&str, &str) -> (u16, Option<&'static str>)) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind stub server");
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(&stream);
+            let mut request_line = String::new();
+            let _ = reader.read_line(&mut request_line);
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+                line.clear();
+            }
+            let mut parts = request_line.split_whitespace();
+            let method = parts.next().unwrap_or_default();
+            let path = parts.next().unwrap_or_default();
+            let (code, location) = route(method, path);
+            let location = location
+                .map(|location| format!("Location: {location}\r\n"))
+                .unwrap_or_default();
+            let response = format!(
+                "HTTP/1.1 {code} Stub\r\n{location}Content-Length: 0\r\nConnection: close\r\n\r\n"
+            );
+            let _ = (&stream).write_all(response.as_bytes());
+        }
+    });
+    format!("http://{address}")
+}
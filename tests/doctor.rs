@@ -0,0 +1,65 @@
+mod common;
+
+use common::Vault;
+use std::fs;
+
+/// `ink doctor` reports malformed notes and `--fix` repairs the ones it safely can.
+#[test]
+fn test_doctor_reports_and_fixes() {
+    let vault = Vault::new();
+    vault.write("valid.md", "---\ntitle: Valid\ntags: [ok]\n---\nFine\n");
+    vault.write(
+        "scalar-tags.md",
+        "---\ntitle: Scalar\ntags: rust\n---\nBody\n",
+    );
+    vault.write(
+        "null-tag.md",
+        "---\ntitle: \"Null\"\ntags: [\"tag\\0prompt\"]\n---\n",
+    );
+    vault.write("untitled.md", "---\ntitle: \"\"\n---\n# From Heading\n");
+    vault.write("broken.md", "---\ntitle: [unclosed\n---\nBody\n");
+    vault.write("plan.md", "---\ntitle: Plan\n---\n");
+    vault.write("projects/plan.md", "---\ntitle: Project Plan\n---\n");
+
+    let report = vault.ink(&["doctor"]);
+    assert_eq!(report.status.code(), Some(1));
+    let stdout = String::from_utf8(report.stdout).unwrap();
+    assert!(stdout
+        .contains("scalar-tags.md\twrong-type\ttags should be a list of strings, found a string"));
+    assert!(stdout.contains("null-tag.md\tnull-byte\ttags contains a null byte"));
+    assert!(stdout.contains("untitled.md\tempty-title\t"));
+    assert!(stdout.contains("broken.md\tinvalid-frontmatter\t"));
+    assert_eq!(stdout.matches("\tduplicate-id\tthe id plan").count(), 2);
+    assert!(!stdout.contains("valid.md"));
+
+    let json = vault.ink(&["doctor", "--json"]);
+    let report: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(report["problems"].as_array().unwrap().len(), 6);
+    assert_eq!(report["problems"][0]["kind"], "invalid-frontmatter");
+
+    let fixed = vault.ink(&["doctor", "--fix"]);
+    assert_eq!(fixed.status.code(), Some(1));
+    let stdout = String::from_utf8(fixed.stdout).unwrap();
+    assert_eq!(stdout.matches("Fixed ").count(), 3, "{stdout}");
+    assert_eq!(
+        vault.read("scalar-tags.md"),
+        "---\ntitle: Scalar\ntags: [rust]\n---\nBody\n"
+    );
+    assert_eq!(
+        vault.read("null-tag.md"),
+        "---\ntitle: \"Null\"\ntags: [tagprompt]\n---\n"
+    );
+    assert_eq!(vault.read("untitled.md"), "---\n---\n# From Heading\n");
+    assert!(stdout.contains("broken.md\tinvalid-frontmatter"));
+    assert!(stdout.contains("duplicate-id"));
+
+    fs::remove_file(vault.notes_dir.join("broken.md")).unwrap();
+    fs::rename(
+        vault.notes_dir.join("plan.md"),
+        vault.notes_dir.join("plan-2.md"),
+    )
+    .unwrap();
+    let clean = vault.ink(&["doctor"]);
+    assert!(clean.status.success());
+    assert!(clean.stdout.is_empty());
+}
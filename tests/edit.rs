@@ -0,0 +1,64 @@
+mod common;
+
+use common::Vault;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+/// A fake editor that prints the arguments it was launched with.
+fn write_fake_editor(dir: &Path, name: &str) -> String {
+    let path = dir.join(name);
+    fs::write(&path, "#!/bin/sh\necho \"$@\"\n").expect("Failed to write fake editor");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+        .expect("Failed to make fake editor executable");
+    path.to_str().unwrap().to_string()
+}
+
+/// `ink edit` opens existing notes in $VISUAL, positioned for the editor in use.
+#[test]
+fn test_edit_positions_editor() {
+    let vault = Vault::new();
+    vault.write(
+        "groceries.md",
+        "---\ntitle: Groceries\n---\n- apples\n- Bananas\n- cherries\n",
+    );
+    vault.write(
+        "recipes.md",
+        "---\ntitle: Recipes\n---\nA pie made with quinces\n",
+    );
+
+    let vim = write_fake_editor(vault.path(), "vim");
+    let code = write_fake_editor(vault.path(), "code");
+    let note = vault.notes_dir.join("groceries.md");
+    let note = note.to_str().unwrap();
+    let stdout = |output: std::process::Output| {
+        assert!(output.status.success());
+        common::stdout(&output).trim().to_string()
+    };
+
+    assert_eq!(
+        stdout(vault.ink_with_env(&["edit", "groceries"], "VISUAL", &vim)),
+        note
+    );
+    assert_eq!(
+        stdout(vault.ink_with_env(&["edit", "Groceries", "+5"], "VISUAL", &vim)),
+        format!("+5 {note}")
+    );
+    assert_eq!(
+        stdout(vault.ink_with_env(
+            &["edit", "groceries", "--search-term", "banana"],
+            "VISUAL",
+            &format!("{code} --wait")
+        )),
+        format!("--wait --goto {note}:5")
+    );
+
+    // Falls back to the top search hit
+    assert!(vault.ink(&["index"]).status.success());
+    assert!(
+        stdout(vault.ink_with_env(&["edit", "quinces"], "VISUAL", &vim)).ends_with("recipes.md")
+    );
+
+    let missing = vault.ink_with_env(&["edit", "zzzz"], "VISUAL", &vim);
+    assert_eq!(missing.status.code(), Some(8));
+}
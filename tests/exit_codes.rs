@@ -0,0 +1,62 @@
+mod common;
+
+use common::Vault;
+use std::fs;
+
+/// Each kind of failure exits with its own code and reports why on stderr.
+#[test]
+fn test_exit_codes() {
+    let vault = Vault::new();
+    vault.write("garden.md", "---\ntitle: Garden\n---\n");
+
+    assert!(vault.ink(&["index"]).status.success());
+
+    let missing = vault.ink(&["show", "zzzz"]);
+    assert_eq!(missing.status.code(), Some(8));
+    assert!(!missing.stderr.is_empty());
+
+    let empty_queue = vault.ink(&["mark", "random"]);
+    assert_eq!(empty_queue.status.code(), Some(1));
+
+    let usage = vault.ink(&["show"]);
+    assert_eq!(usage.status.code(), Some(2));
+
+    let site_file = vault.path().join("site");
+    fs::write(&site_file, "not a folder").unwrap();
+    let unwritable = vault.ink(&["export", "site", site_file.to_str().unwrap()]);
+    assert_eq!(unwritable.status.code(), Some(4));
+
+    let not_bookmark = vault.ink(&["mark", "archive", "garden"]);
+    assert_eq!(not_bookmark.status.code(), Some(5));
+
+    // Nothing listens on port 1, so the connection is refused
+    vault.write(
+        "unreachable.md",
+        "---\ntitle: Unreachable\nurl: http://127.0.0.1:1/post\n---\n",
+    );
+    let unreachable = vault.ink(&["mark", "archive", "unreachable"]);
+    assert_eq!(unreachable.status.code(), Some(7));
+
+    let meta = vault.cache_dir.join("meta.json");
+    let valid_meta = fs::read(&meta).unwrap();
+    fs::write(&meta, "not json").unwrap();
+    let corrupt_index = vault.ink(&["search", "garden"]);
+    assert_eq!(corrupt_index.status.code(), Some(6));
+    fs::write(&meta, valid_meta).unwrap();
+
+    let template = vault.path().join("broken.template.md");
+    fs::write(&template, "{{ note.title").unwrap();
+    vault.write_config(&format!(
+        "note_template = {:?}\n",
+        template.to_str().unwrap()
+    ));
+    let bad_template = vault.ink(&["create", "Orchard"]);
+    assert_eq!(bad_template.status.code(), Some(9));
+
+    vault.write_config("recurse = [not toml");
+    let bad_config = vault.ink(&["list"]);
+    assert_eq!(bad_config.status.code(), Some(3));
+    assert!(String::from_utf8(bad_config.stderr)
+        .unwrap()
+        .contains("Could not load the configuration"));
+}
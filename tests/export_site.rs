@@ -0,0 +1,59 @@
+mod common;
+
+use common::Vault;
+use std::fs;
+
+/// `ink export site` writes a page per visible note with working links and
+/// backlinks, a page per tag and a search index, leaving hidden notes out.
+#[test]
+fn test_export_site() {
+    let vault = Vault::new();
+    vault.write(
+        "index.md",
+        "---\ntitle: Home\ntags:\n    - start\n---\nSee [[plan]], [[old]] and [[private]].\n",
+    );
+    vault.write(
+        "project/plan.md",
+        "# The Plan\n\nBack [home](../index.md).\n",
+    );
+    vault.write("archive/old.md", "Ignored by glob.\n");
+    vault.write(
+        "private.md",
+        "---\ntitle: Private\ntags:\n    - hidden\n---\nSecret.\n",
+    );
+
+    let site_dir = vault.path().join("site");
+    let output = vault.ink(&["export", "site", site_dir.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(common::stdout(&output).starts_with("Exported 2 notes"));
+
+    let site_index = fs::read_to_string(site_dir.join("index.html")).unwrap();
+    assert!(site_index.contains("<h1>Notes</h1>"));
+    assert!(site_index.contains("notes&#x2f;project&#x2f;plan.html\">The Plan</a>"));
+
+    let index = fs::read_to_string(site_dir.join("notes/index.html")).unwrap();
+    assert!(index.contains("<a href=\"../notes/project/plan.html\">plan</a>"));
+    assert!(index.contains("<span class=\"missing\">old</span>"));
+    assert!(index.contains("<span class=\"missing\">private</span>"));
+
+    let plan = fs::read_to_string(site_dir.join("notes/project/plan.html")).unwrap();
+    assert!(plan.contains("<title>The Plan</title>"));
+    assert!(plan.contains("<a href=\"../../notes/index.html\">home</a>"));
+    assert!(plan.contains("<h2>Backlinks</h2>"));
+
+    let tag_page = fs::read_to_string(site_dir.join("tags/start.html")).unwrap();
+    assert!(tag_page.contains(">Home</a>"));
+
+    assert!(!site_dir.join("notes/archive/old.html").exists());
+    assert!(!site_dir.join("notes/private.html").exists());
+
+    let search: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(site_dir.join("search.json")).unwrap()).unwrap();
+    let ids: Vec<&str> = search
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, ["index", "project/plan"]);
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::Vault;
+
+/// Notes without a frontmatter title take their first H1, and section
+/// headings are searchable in their own right.
+#[test]
+fn test_heading_title_and_heading_search() {
+    let vault = Vault::new();
+    vault.write(
+        "2024-03-01.md",
+        "# Weekly Review\n\n## Retrospective\n\nWent well.\n",
+    );
+    vault.write(
+        "frontmatter.md",
+        "---\ntitle: From Frontmatter\n---\n# Ignored Heading\n",
+    );
+    vault.write("plain.md", "No headings here.\n");
+
+    let list = vault.ink_stdout(&["list"]);
+    assert!(list.contains("Weekly Review\t"), "{list}");
+    assert!(list.contains("From Frontmatter\t"), "{list}");
+    assert!(list.contains("plain.md\t"), "{list}");
+
+    vault.ink_stdout(&["index"]);
+    let results = vault.ink_stdout(&["search", "retrospective"]);
+    assert!(results.starts_with("Weekly Review\t"), "{results}");
+}
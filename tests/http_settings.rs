@@ -0,0 +1,87 @@
+mod common;
+
+use common::Vault;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+
+const PAGE: &str = "<html><head><title>A Fetched Page</title></head><body></body></html>";
+
+/// Serve a page titled with the `User-Agent` of each request.
+fn serve_user_agent() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind stub server");
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let mut reader = BufReader::new(&stream);
+            let mut user_agent = String::new();
+            let mut line = String::new();
+            while reader.read_line(&mut line).is_ok_and(|read| read > 2) {
+                if let Some(value) = line.to_lowercase().strip_prefix("user-agent:") {
+                    user_agent = value.trim().to_string();
+                }
+                line.clear();
+            }
+            let html = format!("<html><head><title>{user_agent}</title></head></html>");
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{html}",
+                html.len()
+            );
+            let _ = (&stream).write_all(response.as_bytes());
+        }
+    });
+    format!("http://{address}")
+}
+
+fn setup(config: &str) -> Vault {
+    let vault = Vault::new();
+    vault.write_config(config);
+    vault
+}
+
+/// `--offline` creates the bookmark without fetching and says why the title is the url.
+#[test]
+fn test_offline() {
+    let base_url = common::serve_html(PAGE);
+    let vault = setup("");
+    let url = format!("{base_url}/page");
+
+    let created = vault.ink(&["mark", "create", &url, "--offline"]);
+    assert!(created.status.success());
+    assert!(String::from_utf8_lossy(&created.stderr).contains("offline mode is on"));
+    assert_eq!(common::stdout(&created), format!("{url}\n"));
+
+    let checked = vault.ink(&["mark", "check", "--offline"]);
+    assert!(!checked.status.success());
+
+    let vault = setup("[http]\noffline = true\n");
+    let created = vault.ink(&["mark", "create", &url]);
+    assert_eq!(common::stdout(&created), format!("{url}\n"));
+}
+
+/// The `[http]` settings apply to page fetches, and a bookmark whose page
+/// cannot be fetched is not saved.
+#[test]
+fn test_http_settings() {
+    let base_url = common::serve_html(PAGE);
+    let vault = setup("[http]\nmax_body_size = 10\n");
+    let created = vault.ink(&["mark", "create", &format!("{base_url}/big")]);
+    assert_eq!(created.status.code(), Some(7));
+    assert!(String::from_utf8(created.stderr)
+        .unwrap()
+        .contains("the page is larger than 10 bytes"));
+    assert_eq!(fs::read_dir(&vault.notes_dir).unwrap().count(), 0);
+
+    let base_url = serve_user_agent();
+    let vault = setup("[http]\nuser_agent = \"Reading Bot\"\n");
+    let created = vault.ink(&["mark", "create", &base_url]);
+    assert_eq!(common::stdout(&created), "reading bot\n");
+
+    let vault = setup("[http]\nproxy = \"not a proxy\"\n");
+    let created = vault.ink(&["mark", "create", &base_url]);
+    assert_eq!(created.status.code(), Some(3));
+    assert!(String::from_utf8(created.stderr)
+        .unwrap()
+        .contains("invalid [http] settings"));
+}
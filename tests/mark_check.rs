@@ -0,0 +1,106 @@
+mod common;
+
+use common::Vault;
+use serde_json::Value;
+use std::path::Path;
+
+fn route(method: &str, path: &str) -> (u16, Option<&'static str>) {
+    match (method, path) {
+        (_, "/ok") | ("GET", "/no-head") => (200, None),
+        (_, "/moved") => (301, Some("/ok")),
+        (_, "/elsewhere") => (302, Some("/ok")),
+        (_, "/down") => (503, None),
+        (_, "/busy") => (429, None),
+        ("HEAD", "/no-head") => (405, None),
+        _ => (404, None),
+    }
+}
+
+fn write_bookmark(vault: &Vault, name: &str, url: &str) {
+    vault.write(
+        &format!("{name}.md"),
+        format!("---\ntitle: {name}\nurl: {url}\ntags:\n- reading\n---\n"),
+    );
+}
+
+/// `ink mark check` finds dead links and permanent redirects, and can fix them.
+#[test]
+fn test_mark_check() {
+    let base_url = common::serve_routes(route);
+    let vault = Vault::new();
+
+    for name in [
+        "ok",
+        "moved",
+        "elsewhere",
+        "down",
+        "busy",
+        "no-head",
+        "gone",
+    ] {
+        write_bookmark(&vault, name, &format!("{base_url}/{name}"));
+    }
+    // .invalid names never resolve
+    write_bookmark(&vault, "unresolvable", "http://unresolvable.invalid/post");
+    vault.write("plain.md", "# Not a bookmark\n");
+
+    let checked = vault.ink(&["mark", "check", "--json", "--concurrency", "3"]);
+    assert!(!checked.status.success());
+    let report: Value = serde_json::from_slice(&checked.stdout).unwrap();
+    let statuses: Vec<(String, String)> = report
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|check| {
+            let path = check["path"].as_str().unwrap();
+            let name = Path::new(path).file_stem().unwrap().to_str().unwrap();
+            (
+                name.to_string(),
+                check["status"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect();
+    let expected = [
+        ("busy", "error"),
+        ("down", "error"),
+        ("elsewhere", "ok"),
+        ("gone", "broken"),
+        ("moved", "moved"),
+        ("no-head", "ok"),
+        ("ok", "ok"),
+        ("unresolvable", "dns"),
+    ];
+    assert_eq!(
+        statuses,
+        expected.map(|(name, status)| (name.to_string(), status.to_string()))
+    );
+    assert_eq!(report[1]["code"], 503);
+    assert_eq!(report[4]["location"], format!("{base_url}/ok"));
+
+    let fixed = vault.ink(&["mark", "check", "--tag-dead", "--fix-redirects"]);
+    assert!(!fixed.status.success());
+    let stdout = String::from_utf8(fixed.stdout).unwrap();
+    assert!(stdout.contains(&format!(
+        "404\t{}",
+        vault.notes_dir.join("gone.md").display()
+    )));
+    assert!(stdout.contains(&format!(
+        "moved\t{}",
+        vault.notes_dir.join("moved.md").display()
+    )));
+    assert!(!stdout.contains("ok.md"));
+
+    let gone = vault.read("gone.md");
+    assert!(gone.contains("- reading\n- dead-link\n"));
+    let moved = vault.read("moved.md");
+    assert!(moved.contains(&format!("url: {base_url}/ok\n")));
+    let ok = vault.read("ok.md");
+    assert!(!ok.contains("dead-link"));
+
+    let dead = vault.ink(&["search", "#dead-link"]);
+    let dead = String::from_utf8(dead.stdout).unwrap();
+    assert!(dead.contains("gone.md") && dead.contains("unresolvable.md"));
+    // Servers that are down or busy may come back
+    assert!(!dead.contains("down.md") && !dead.contains("busy.md"));
+    assert!(!dead.contains("moved.md"));
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use common::Vault;
+use std::fs;
+
+const PAGE: &str = "<html><head><title>Async Rust</title></head><body></body></html>";
+
+/// Saving a url that differs only in tracking parameters, a trailing slash or
+/// a fragment finds the existing bookmark, and `ink mark dedupe` merges
+/// duplicates made another way.
+#[test]
+fn test_mark_dedupe() {
+    let base_url = common::serve_html(PAGE);
+    let vault = Vault::new();
+
+    let created = vault.ink(&["mark", "create", &format!("{base_url}/async")]);
+    assert!(created.status.success());
+    let again = vault.ink(&[
+        "mark",
+        "create",
+        &format!("{base_url}/async/?utm_source=feed#intro"),
+    ]);
+    assert!(again.status.success());
+    assert!(String::from_utf8(again.stderr)
+        .unwrap()
+        .contains("is already bookmarked in"));
+    assert_eq!(fs::read_dir(&vault.notes_dir).unwrap().count(), 1);
+
+    // Another page with the same title gets the next free id
+    let same_title = vault.ink(&["mark", "create", "--later", &format!("{base_url}/talk")]);
+    assert!(same_title.status.success());
+    let talk = vault.read("async-rust-2.md");
+    assert!(talk.contains(&format!("url: {base_url}/talk\n")));
+    assert!(talk.contains("read: false\n"));
+    let first = vault.read("async-rust.md");
+    assert!(!first.contains("read:"));
+
+    vault.write("async-copy.md", format!("---\ntitle: Async copy\nurl: {base_url}/async?fbclid=1\ntags:\n- bookmark\n- tokio\ncreated: 2020-01-01\n---\nRead the pinning chapter\n"));
+    vault.write(
+        "other.md",
+        format!("---\ntitle: Other\nurl: {base_url}/other\n---\n"),
+    );
+    assert!(vault.ink(&["index"]).status.success());
+
+    let dry_run = vault.ink(&["mark", "dedupe", "--dry-run"]);
+    assert!(String::from_utf8(dry_run.stdout)
+        .unwrap()
+        .contains("Would merge"));
+    assert!(vault.notes_dir.join("async-rust.md").exists());
+
+    let deduped = vault.ink(&["mark", "dedupe"]);
+    assert!(deduped.status.success());
+    let stdout = String::from_utf8(deduped.stdout).unwrap();
+    // The older bookmark is the one kept
+    assert!(stdout.contains(&format!(
+        "Merged {} into {}",
+        vault.notes_dir.join("async-rust.md").display(),
+        vault.notes_dir.join("async-copy.md").display()
+    )));
+    assert!(!vault.notes_dir.join("async-rust.md").exists());
+    assert!(vault.notes_dir.join("other.md").exists());
+    let merged = vault.read("async-copy.md");
+    assert!(merged.contains("- bookmark\n- tokio\n"));
+    assert!(merged.contains("Read the pinning chapter"));
+
+    let search = common::stdout(&vault.ink(&["search", "#bookmark"]));
+    assert!(search.contains("async-copy.md"));
+    assert!(!search.contains("async-rust.md"));
+
+    let nothing = vault.ink(&["mark", "dedupe"]);
+    assert!(nothing.status.success());
+    assert!(nothing.stdout.is_empty());
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::Vault;
+
+/// `ink mark export` writes bookmarks in other tools' formats, filtered by tag or query.
+#[test]
+fn test_mark_export() {
+    let vault = Vault::new();
+
+    vault.write("tokio.md", "---\ntitle: Tokio tutorial\nurl: https://tokio.rs/tokio/tutorial\ntags:\n- bookmark\n- rust\ncreated: 2023-11-14T22:13:20Z\n---\nStart with the mini-redis chapter\n\n## Snapshot\n\nArchived text\n");
+    vault.write("sourdough.md", "---\ntitle: Sourdough\nurl: https://example.com/bread\ntags:\n- bookmark\n- baking\ndescription: A starter guide\n---\n");
+    vault.write("plain.md", "# Not a bookmark\n");
+    vault.ink_stdout(&["index"]);
+
+    let netscape = vault.ink_stdout(&["mark", "export"]);
+    assert!(netscape.contains(
+        "<DT><A HREF=\"https://tokio.rs/tokio/tutorial\" ADD_DATE=\"1700000000\" TAGS=\"bookmark,rust\">Tokio tutorial</A>\n    <DD>Start with the mini-redis chapter\n"
+    ));
+    assert!(netscape.contains("<DD>A starter guide\n"));
+    assert!(!netscape.contains("Archived text"));
+    assert!(!netscape.contains("Not a bookmark"));
+
+    let csv = vault.ink_stdout(&["mark", "export", "--format", "csv", "--tags", "baking"]);
+    assert!(csv.starts_with(
+        "url,title,description,tags,created\nhttps://example.com/bread,Sourdough,A starter guide,\"baking,bookmark\","
+    ));
+    assert_eq!(csv.lines().count(), 2);
+
+    let feed = vault.ink_stdout(&["mark", "export", "-f", "jsonfeed", "redis"]);
+    let feed: serde_json::Value = serde_json::from_str(&feed).unwrap();
+    let items = feed["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["url"], "https://tokio.rs/tokio/tutorial");
+
+    let opml = vault.ink_stdout(&["mark", "export", "--format", "opml"]);
+    assert!(opml
+        .contains("<outline type=\"link\" text=\"Sourdough\" url=\"https://example.com/bread\""));
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::Vault;
+use std::fs;
+
+const PAGE: &str = "<html><head><title>Fetched Title</title></head><body></body></html>";
+
+fn export(base_url: &str) -> String {
+    format!(
+        r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3>Dev Tools</H3>
+    <DL><p>
+        <DT><A HREF="{base_url}/ripgrep" ADD_DATE="1700000000" TAGS="cli">ripgrep</A>
+        <DD>Fast grep
+        <DT><A HREF="{base_url}/ripgrep/?utm_source=twitter">ripgrep again</A>
+    </DL><p>
+    <DT><A HREF="{base_url}/fd">fd</A>
+    <DT><A HREF="place:sort=8">Recent</A>
+</DL><p>
+"#
+    )
+}
+
+/// `ink mark import` turns a browser export into bookmark notes.
+#[test]
+fn test_mark_import() {
+    let base_url = common::serve_html(PAGE);
+    let vault = Vault::new();
+    let export_path = vault.path().join("bookmarks.html");
+    fs::write(&export_path, export(&base_url)).unwrap();
+    vault.write(
+        "fd.md",
+        format!("---\ntitle: fd\nurl: {base_url}/fd/\n---\n"),
+    );
+
+    let imported = vault.ink(&[
+        "mark",
+        "import",
+        export_path.to_str().unwrap(),
+        "--no-fetch",
+    ]);
+    assert!(imported.status.success());
+    assert!(String::from_utf8(imported.stderr)
+        .unwrap()
+        .ends_with("1 created, 2 duplicates, 1 skipped\n"));
+    let content = vault.read("ripgrep.md");
+    assert!(content.starts_with("---\ntitle: \"ripgrep\"\ntags:\n"));
+    for tag in ["bookmark", "cli", "dev-tools"] {
+        assert!(content.contains(&format!("    - {tag}\n")));
+    }
+    assert!(content.ends_with(&format!(
+        "url: {base_url}/ripgrep\ncreated: 2023-11-14T22:13:20Z\n---\nFast grep\n"
+    )));
+
+    let search = common::stdout(&vault.ink(&["search", "#dev-tools"]));
+    assert!(search.contains("ripgrep.md"));
+
+    // Importing again creates nothing, and titles are fetched by default
+    fs::remove_file(vault.notes_dir.join("fd.md")).unwrap();
+    let again = vault.ink(&["mark", "import", export_path.to_str().unwrap()]);
+    assert!(String::from_utf8(again.stderr)
+        .unwrap()
+        .ends_with("1 created, 2 duplicates, 1 skipped\n"));
+    let fetched = vault.read("fetched-title.md");
+    assert!(fetched.contains(&format!("url: {base_url}/fd\n")));
+}
+
+/// `ink mark import --from` reads bookmark service exports without fetching pages.
+#[test]
+fn test_mark_import_from_services() {
+    let vault = Vault::new();
+
+    // Unresolvable hosts, so any fetch would fail the title assertions below
+    let pinboard = vault.path().join("pinboard.json");
+    fs::write(
+        &pinboard,
+        r#"[
+            {"href":"https://pinned.invalid/post","description":"Pinned post","extended":"Read the second half","time":"2023-11-14T22:13:20Z","toread":"yes","tags":"rust async"},
+            {"href":"https://pinned.invalid/post/?utm_source=rss","description":"Same post","extended":"","time":"2023-11-15T00:00:00Z","toread":"no","tags":""}
+        ]"#,
+    )
+    .unwrap();
+    let imported = vault.ink(&[
+        "mark",
+        "import",
+        "--from",
+        "pinboard",
+        pinboard.to_str().unwrap(),
+    ]);
+    assert!(imported.status.success());
+    assert!(String::from_utf8(imported.stderr)
+        .unwrap()
+        .ends_with("1 created, 1 duplicates, 0 skipped\n"));
+    let content = vault.read("pinned-post.md");
+    assert!(content.contains("    - async\n"));
+    assert!(content.contains("created: 2023-11-14T22:13:20Z\n"));
+    assert!(content.contains("read: false\n"));
+    assert!(content.ends_with("---\nRead the second half\n"));
+
+    let pocket = vault.path().join("pocket.csv");
+    fs::write(
+        &pocket,
+        "title,url,time_added,cursor,tags,status\nPocketed,https://pocket.invalid/a,1700000000,,later|long read,archive\nBroken row,,,,,unread\n",
+    )
+    .unwrap();
+    let imported = vault.ink(&[
+        "mark",
+        "import",
+        "--from",
+        "pocket",
+        pocket.to_str().unwrap(),
+    ]);
+    assert!(String::from_utf8(imported.stderr)
+        .unwrap()
+        .ends_with("1 created, 0 duplicates, 1 skipped\n"));
+    let content = vault.read("pocketed.md");
+    assert!(content.contains("    - long-read\n"));
+    assert!(!content.contains("read: false"));
+
+    let raindrop = vault.path().join("raindrop.csv");
+    fs::write(
+        &raindrop,
+        "id,title,note,excerpt,url,folder,tags,created,cover,highlights,favorite\n1,Dropped,,A page summary,https://raindrop.invalid,Unsorted,\"web, css\",2023-11-14T22:13:20.000Z,,,false\n",
+    )
+    .unwrap();
+    let imported = vault.ink(&[
+        "mark",
+        "import",
+        "--from",
+        "raindrop",
+        raindrop.to_str().unwrap(),
+    ]);
+    assert!(imported.status.success());
+    let content = vault.read("dropped.md");
+    assert!(content.contains("description: \"A page summary\"\n"));
+    assert!(content.contains("    - css\n"));
+
+    let invalid = vault.ink(&[
+        "mark",
+        "import",
+        "--from",
+        "pinboard",
+        pocket.to_str().unwrap(),
+    ]);
+    assert!(!invalid.status.success());
+}
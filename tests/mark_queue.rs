@@ -0,0 +1,58 @@
+mod common;
+
+use common::Vault;
+
+const PAGE: &str = "<html><head><title>Async Rust</title></head><body></body></html>";
+
+/// Bookmarks saved with `--later` are listed by `ink mark queue` oldest first,
+/// and `ink mark read` takes them off the queue and out of `read:false` searches.
+#[test]
+fn test_mark_queue() {
+    let base_url = common::serve_html(PAGE);
+    let vault = Vault::new();
+
+    let empty = vault.ink(&["mark", "random"]);
+    assert!(!empty.status.success());
+
+    vault.write("older.md", "---\ntitle: Older\nurl: https://example.com/older\nread: false\ncreated: 2020-01-01\n---\n");
+    vault.write(
+        "finished.md",
+        "---\ntitle: Finished\nurl: https://example.com/finished\nread: true\n---\n",
+    );
+    let created = vault.ink(&["mark", "create", "--later", &format!("{base_url}/async")]);
+    assert!(created.status.success());
+    let saved = vault.read("async-rust.md");
+    assert!(saved.contains("read: false\n"));
+
+    let queue = vault.ink_stdout(&["mark", "queue"]);
+    assert_eq!(common::first_fields(&queue), ["older", "async-rust"]);
+
+    let random = common::stdout(&vault.ink(&["mark", "random", "--json"]));
+    let picked: serde_json::Value = serde_json::from_str(&random).unwrap();
+    assert_eq!(picked[0]["read"], false);
+
+    assert!(vault.ink(&["index"]).status.success());
+    let unread = common::stdout(&vault.ink(&["search", "read:false"]));
+    assert!(unread.contains("older.md"));
+    assert!(!unread.contains("finished.md"));
+
+    vault.write("journal.md", "---\ntitle: Journal\n---\n");
+    let not_bookmark = vault.ink(&["mark", "read", "journal"]);
+    assert_eq!(not_bookmark.status.code(), Some(5));
+    assert_eq!(vault.read("journal.md"), "---\ntitle: Journal\n---\n");
+
+    let read = vault.ink(&["mark", "read", "older"]);
+    assert!(read.status.success());
+    let older = vault.read("older.md");
+    assert!(older.contains("read: true\n"));
+    assert!(older.contains("read_at: "));
+
+    let queue = common::stdout(&vault.ink(&["mark", "queue"]));
+    assert!(!queue.contains("older"));
+    let unread = common::stdout(&vault.ink(&["search", "read:false"]));
+    assert!(!unread.contains("older.md"));
+    assert!(unread.contains("async-rust.md"));
+    let finished = common::stdout(&vault.ink(&["search", "read:true"]));
+    assert!(finished.contains("older.md"));
+    assert!(finished.contains("finished.md"));
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::Vault;
+
+/// `ink resolve` finds a note by path, id, title, prefix or fuzzy title match.
+#[test]
+fn test_resolve_note() {
+    let vault = Vault::new();
+    for (name, title) in [
+        ("rust-ownership.md", "Rust Ownership"),
+        ("rust-lifetimes.md", "Rust Lifetimes"),
+        ("projects/garden.md", "Garden Plans"),
+    ] {
+        vault.write(name, format!("---\ntitle: {title}\n---\nBody\n"));
+    }
+
+    let index = vault.ink(&["index"]);
+    assert!(index.status.success());
+
+    let resolved = |query: &str| vault.ink_stdout(&["resolve", query]).trim().to_string();
+    let garden = vault.notes_dir.join("projects/garden.md");
+    let garden = garden.to_str().unwrap();
+    let ownership = vault.notes_dir.join("rust-ownership.md");
+    let ownership = ownership.to_str().unwrap();
+
+    assert_eq!(resolved("projects/garden"), garden);
+    assert_eq!(resolved("garden"), garden);
+    assert_eq!(resolved("garden plans"), garden);
+    assert_eq!(resolved("gard"), garden);
+    assert_eq!(resolved("Rust Ownership"), ownership);
+
+    let ambiguous = vault.ink(&["resolve", "rust"]);
+    assert_eq!(ambiguous.status.code(), Some(8));
+    assert!(ambiguous.stdout.is_empty());
+    let stderr = String::from_utf8(ambiguous.stderr).unwrap();
+    assert!(stderr.contains("rust-ownership.md"));
+    assert!(stderr.contains("rust-lifetimes.md"));
+
+    let missing = vault.ink(&["resolve", "zzz"]);
+    assert_eq!(missing.status.code(), Some(8));
+    assert!(String::from_utf8(missing.stderr)
+        .unwrap()
+        .contains("No note found for zzz"));
+
+    // Misspelled titles resolve through the typeahead index
+    assert_eq!(resolved("Ownrship"), ownership);
+}
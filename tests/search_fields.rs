@@ -0,0 +1,40 @@
+mod common;
+
+use common::{first_fields, Vault};
+
+/// Code blocks, link URLs and image alt text get their own fields, so they can
+/// be searched directly without matching plain body searches.
+#[test]
+fn test_search_markdown_fields() {
+    let vault = Vault::new();
+    vault.write("code.md", "# Rust Entry\n\n```rust\nfn main() {}\n```\n");
+    vault.write(
+        "prose.md",
+        "# Prose\n\nThe main fn is where it begins, see [the site](https://example.com/start).\n\n![a lighthouse at dusk](photo.jpg)\n",
+    );
+
+    assert!(vault.ink(&["index"]).status.success());
+
+    assert_eq!(
+        first_fields(&vault.ink_stdout(&["search", "code:\"fn main\""])),
+        ["Rust Entry"]
+    );
+    assert_eq!(
+        first_fields(&vault.ink_stdout(&["search", "lang:rust"])),
+        ["Rust Entry"]
+    );
+    assert_eq!(
+        first_fields(&vault.ink_stdout(&["search", "main"])),
+        ["Prose"]
+    );
+    assert!(first_fields(&vault.ink_stdout(&["search", "example"])).is_empty());
+    assert_eq!(
+        first_fields(&vault.ink_stdout(&["search", "links:example"])),
+        ["Prose"]
+    );
+    assert!(first_fields(&vault.ink_stdout(&["search", "lighthouse"])).is_empty());
+    assert_eq!(
+        first_fields(&vault.ink_stdout(&["search", "image_alt:lighthouse"])),
+        ["Prose"]
+    );
+}
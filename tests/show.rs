@@ -0,0 +1,34 @@
+mod common;
+
+use common::Vault;
+
+/// `ink show` finds a note by id or title and renders it without a pager.
+#[test]
+fn test_show_renders_terminal_and_html() {
+    let vault = Vault::new();
+    vault.write(
+        "meeting-notes.md",
+        "---\ntitle: Meeting Notes\ntags:\n    - work\n---\nDiscussed **budget**.\n",
+    );
+
+    let by_title = vault.ink(&["show", "meeting notes", "--color", "never"]);
+    assert!(by_title.status.success());
+    assert_eq!(
+        String::from_utf8(by_title.stdout).unwrap(),
+        "# Meeting Notes\n\nDiscussed budget.\n"
+    );
+
+    let colored = vault.ink(&["show", "meeting-notes", "--color", "always"]);
+    assert!(String::from_utf8(colored.stdout)
+        .unwrap()
+        .contains("\x1b[1mbudget\x1b[0m"));
+
+    let html = vault.ink(&["show", "meeting-notes", "--html"]);
+    let html = String::from_utf8(html.stdout).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<title>Meeting Notes</title>"));
+    assert!(html.contains("<p>Discussed <strong>budget</strong>.</p>"));
+
+    let missing = vault.ink(&["show", "nope"]);
+    assert_eq!(missing.status.code(), Some(8));
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use common::Vault;
+
+/// `ink tag` should only rewrite the `tags:` key, leaving the rest of the file intact,
+/// and keep the search index in sync.
+#[test]
+fn test_tag_add_rename_and_remove() {
+    let vault = Vault::new();
+    vault.write(
+        "tagged-note.md",
+        "---\ntitle: 'Tagged Note'  # keep me\ntags:\n  - draft\nurl: https://example.com\n---\n\nBody text.\n",
+    );
+
+    vault.ink_stdout(&["index"]);
+    vault.ink_stdout(&["tag", "add", "tagged-note", "review", "#ideas"]);
+    vault.ink_stdout(&["tag", "rename", "draft", "final"]);
+    vault.ink_stdout(&["tag", "rm", "--query", "#ideas", "review"]);
+
+    let content = vault.read("tagged-note.md");
+    assert_eq!(
+        content,
+        "---\ntitle: 'Tagged Note'  # keep me\ntags:\n  - final\n  - ideas\nurl: https://example.com\n---\n\nBody text.\n"
+    );
+
+    let stdout = vault.ink_stdout(&["search", "#final"]);
+    assert!(
+        stdout.contains("Tagged Note"),
+        "Index should reflect the renamed tag, got: {stdout}"
+    );
+}
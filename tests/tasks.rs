@@ -0,0 +1,45 @@
+mod common;
+
+use common::{stdout, Vault};
+
+/// Tasks are indexed with their note and line, can be filtered by state,
+/// due date and tag, and `ink tasks done` ticks the box in the source file.
+#[test]
+fn test_tasks_list_filter_and_done() {
+    let vault = Vault::new();
+    vault.write(
+        "projects/plan.md",
+        "---\ntitle: Plan\n---\n# Plan\n\n- [ ] Draft spec @due(2026-10-20) #work\n- [x] Book room #work\n- [ ] Water plants #home\n",
+    );
+
+    assert!(vault.ink(&["index"]).status.success());
+
+    let open = stdout(&vault.ink(&["tasks", "--open"]));
+    assert_eq!(
+        open,
+        "projects/plan:6\t[ ] Draft spec @due(2026-10-20) #work\nprojects/plan:8\t[ ] Water plants #home\n"
+    );
+
+    let work = stdout(&vault.ink(&["tasks", "#work"]));
+    assert_eq!(work.lines().count(), 2, "{work}");
+
+    let due = stdout(&vault.ink(&["tasks", "--due-before", "2026-11-01"]));
+    assert_eq!(due.lines().count(), 1, "{due}");
+    assert!(due.starts_with("projects/plan:6\t"), "{due}");
+
+    // Searching notes does not return task documents
+    let search = stdout(&vault.ink(&["search", "plants"]));
+    assert_eq!(search.lines().count(), 1, "{search}");
+    assert!(search.starts_with("Plan\t"), "{search}");
+
+    let done = vault.ink(&["tasks", "done", "projects/plan:6"]);
+    assert!(done.status.success());
+    let content = vault.read("projects/plan.md");
+    assert!(content.contains("- [x] Draft spec @due(2026-10-20) #work\n"));
+
+    let open = stdout(&vault.ink(&["tasks", "--open"]));
+    assert_eq!(open, "projects/plan:8\t[ ] Water plants #home\n");
+
+    let missing = vault.ink(&["tasks", "done", "projects/plan:2"]);
+    assert_eq!(missing.status.code(), Some(1));
+}
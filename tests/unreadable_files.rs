@@ -0,0 +1,69 @@
+mod common;
+
+use common::Vault;
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+
+/// Files that are not UTF-8 are skipped with a warning instead of failing the
+/// whole command, and notes with non-UTF-8 names are still found.
+#[test]
+fn test_walk_skips_unreadable_files() {
+    let vault = Vault::new();
+    vault.write("garden.md", "---\ntitle: Garden\n---\nTomatoes\n");
+    vault.write("image.md", [0x89, b'P', b'N', b'G', 0xff, 0x00]);
+    vault.write("cafe.md", b"---\ntitle: Caf\xe9\n---\nEspresso\n");
+    fs::write(
+        vault.notes_dir.join(OsStr::from_bytes(b"r\xe9sum\xe9.md")),
+        "---\ntitle: Resume\n---\nExperience\n",
+    )
+    .unwrap();
+
+    let index = vault.ink(&["index"]);
+    let stderr = String::from_utf8(index.stderr).unwrap();
+    assert!(index.status.success(), "{stderr}");
+    let stdout = String::from_utf8(index.stdout).unwrap();
+    assert!(stdout.contains("Indexed 2 documents"), "{stdout}");
+    assert!(stderr.contains("image.md: not valid UTF-8"), "{stderr}");
+    assert!(stderr.contains("cafe.md: not valid UTF-8"), "{stderr}");
+    assert!(stderr.contains("Skipped 2 files that could not be read"));
+
+    let stdout = vault.ink_stdout(&["list"]);
+    assert!(stdout.contains("Garden\t"));
+    assert!(stdout.contains("Resume\t"));
+    assert!(!stdout.contains("Caf"));
+
+    let stdout = vault.ink_stdout(&["search", "experience"]);
+    assert!(stdout.contains("Resume"), "{stdout}");
+
+    let lossy = vault.ink_with_env(&["list"], "INK_LOSSY_UTF8", "true");
+    assert!(lossy.status.success());
+    let stdout = String::from_utf8(lossy.stdout).unwrap();
+    let stderr = String::from_utf8(lossy.stderr).unwrap();
+    assert!(stdout.contains("Caf\u{fffd}\t"), "{stdout}");
+    assert!(!stderr.contains("Skipped"), "{stderr}");
+}
+
+/// `lossy_utf8` never lets a command write the replaced bytes back to a note.
+#[test]
+fn test_lossy_utf8_is_read_only() {
+    let vault = Vault::new();
+    let latin1 = b"---\ntitle: Caf\xe9\n---\nEspresso\n";
+    vault.write("cafe.md", latin1);
+
+    let shown = vault.ink_with_env(&["show", "cafe"], "INK_LOSSY_UTF8", "true");
+    assert!(shown.status.success());
+    let stdout = String::from_utf8(shown.stdout).unwrap();
+    assert!(stdout.contains("Espresso"), "{stdout}");
+
+    for args in [
+        ["tag", "add", "cafe", "coffee"].as_slice(),
+        &["append", "cafe", "Flat white"],
+    ] {
+        let written = vault.ink_with_env(args, "INK_LOSSY_UTF8", "true");
+        assert!(!written.status.success());
+        let stderr = String::from_utf8(written.stderr).unwrap();
+        assert!(stderr.contains("cafe.md: not valid UTF-8"), "{stderr}");
+    }
+    assert_eq!(fs::read(vault.notes_dir.join("cafe.md")).unwrap(), latin1);
+}